@@ -1,22 +1,432 @@
-use clap::{Parser, ValueEnum};
+use crate::{
+    detect_ci_metadata, FilterTag, GitInfo, Outcome, RedactOptions, TestCaseFilter, TruncateOptions,
+};
+use clap::{Parser, Subcommand, ValueEnum};
+use regex::Regex;
+use std::io;
+use std::path::{Path, PathBuf};
 
+/// Elements removed by [`crate::TestSuitesOrTestSuite::filter_tags`].
+/// [`crate::FilterTag`] can select any element or attribute.
 #[derive(Clone, ValueEnum, Debug)]
 pub enum PossibleFilterTags {
     SystemOut,
     SystemErr,
 }
 
+/// Outcomes kept by `--only-outcome`. See [`crate::Outcome`].
+#[derive(Clone, ValueEnum, Debug)]
+pub enum PossibleOutcomes {
+    Passed,
+    Failed,
+    Errored,
+    Skipped,
+    Disabled,
+    Flaky,
+}
+impl From<&PossibleOutcomes> for Outcome {
+    fn from(outcome: &PossibleOutcomes) -> Self {
+        match outcome {
+            PossibleOutcomes::Passed => Outcome::Passed,
+            PossibleOutcomes::Failed => Outcome::Failed,
+            PossibleOutcomes::Errored => Outcome::Errored,
+            PossibleOutcomes::Skipped => Outcome::Skipped,
+            PossibleOutcomes::Disabled => Outcome::Disabled,
+            PossibleOutcomes::Flaky => Outcome::Flaky,
+        }
+    }
+}
+
+/// JSON shape of `skipped`, `error` and `failure` in `testcase`.
+///
+/// `Object` keeps only the first element and is compatible with previous versions.
+/// `Array` keeps all elements.
+#[derive(Clone, Copy, ValueEnum, Debug, Default)]
+pub enum DetailShape {
+    #[default]
+    Object,
+    Array,
+}
+
+/// Which part of a truncated text is kept. See [`crate::TruncateOptions`].
+///
+/// `Head` keeps the beginning like the message of a stack trace.
+/// `Tail` keeps the end like the last lines of a log.
+/// `Both` keeps both halves and cuts the middle.
+#[derive(Clone, ValueEnum, Debug, Default)]
+pub enum TruncateKeep {
+    #[default]
+    Head,
+    Tail,
+    Both,
+}
+
+/// Which escape sequences and characters are stripped from texts. See [`crate::TestSuitesOrTestSuite::strip_texts`].
+///
+/// `Ansi` strips ANSI escape sequences like colors.
+/// `OrphanSgr` strips SGR sequences whose ESC is lost like `[31m` too, except the ones followed by `]` like `[5m]`.
+/// `Control` strips other control characters too, except tab, line feed and carriage return.
+#[derive(Clone, ValueEnum, Debug)]
+pub enum StripMode {
+    Ansi,
+    OrphanSgr,
+    Control,
+}
+
+/// How the JSON of files is output.
+///
+/// `Document` outputs one JSON document per file and is compatible with previous versions.
+/// `Array` outputs one JSON array of all files.
+/// `Ndjson` outputs one JSON per line for each file.
+#[derive(Clone, ValueEnum, Debug, Default)]
+pub enum OutputMode {
+    #[default]
+    Document,
+    Array,
+    Ndjson,
+}
+
+/// What to do with `tests`, `failures`, `errors`, `skipped`, `disabled` and `time` of testsuites that disagree with their testcases.
+///
+/// `Fix` recomputes them from the testcases, after the testcases are filtered by `--only-*` and `--min-time`.
+/// `time` becomes the sum of the kept testcases.
+/// `Warn` prints each mismatch to stderr and keeps them. `time` is reported only when it is shorter than a testcase.
+#[derive(Clone, ValueEnum, Debug)]
+pub enum CountersMode {
+    Fix,
+    Warn,
+}
+
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
+#[command(args_conflicts_with_subcommands = true)]
 pub struct Args {
-    /// JUnit XML path
-    pub path: String,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// JUnit XML paths, glob patterns like "build/**/TEST-*.xml" or directories to find *.xml recursively. Read from stdin when it is omitted or "-"
+    pub paths: Vec<String>,
 
     /// Output pretty JSON
     #[arg(short, long, default_value = "false")]
     pub pretty: bool,
 
-    /// Filter XML tag names
-    #[arg(short, long, value_enum)]
-    pub filter_tags: Option<Vec<PossibleFilterTags>>,
+    /// How to output the JSON of files. Each JSON is tagged with "path" of its file unless a single file is output as a document
+    #[arg(long, value_enum, default_value = "document", conflicts_with_all = ["stream", "flat"])]
+    pub output: OutputMode,
+
+    /// Remove elements or attributes like system-out, properties, testcase.file or failure.inner. "[scope.]field" with the names of the JSON
+    #[arg(short, long, value_name = "SELECTOR", value_delimiter = ',')]
+    pub filter_tags: Option<Vec<FilterTag>>,
+
+    /// JSON shape of skipped, error and failure in testcase
+    #[arg(long, value_enum, default_value = "object")]
+    pub detail_shape: DetailShape,
+
+    /// Keep unrecognized XML attributes and tags in "extra"
+    #[arg(long, default_value = "false")]
+    pub lossless: bool,
+
+    /// Recover from malformed or truncated XML and print warnings to stderr
+    #[arg(long, default_value = "false")]
+    pub lenient: bool,
+
+    /// Add "outcome" of each testcase, one of passed, failed, errored, skipped, disabled and flaky
+    #[arg(long, default_value = "false")]
+    pub outcome: bool,
+
+    /// Keep only testcases of the outcomes
+    #[arg(long, value_enum, value_delimiter = ',')]
+    pub only_outcome: Vec<PossibleOutcomes>,
+
+    /// Keep only testcases whose name matches the regex
+    #[arg(long, value_name = "REGEX")]
+    pub only_name: Option<Regex>,
+
+    /// Keep only testcases whose classname matches the regex
+    #[arg(long, value_name = "REGEX")]
+    pub only_classname: Option<Regex>,
+
+    /// Keep only testcases whose file matches the glob pattern
+    #[arg(long, value_name = "GLOB")]
+    pub only_file: Option<glob::Pattern>,
+
+    /// Keep only testcases that take at least the seconds
+    #[arg(long, value_name = "SECONDS")]
+    pub min_time: Option<f32>,
+
+    /// Redact AWS keys, GitHub tokens, JWTs, bearer tokens and private keys in every string
+    #[arg(long, default_value = "false")]
+    pub redact: bool,
+
+    /// Redact matches of the regex, or only its `secret` group, in every string. It can be repeated
+    #[arg(long, value_name = "REGEX")]
+    pub redact_pattern: Vec<Regex>,
+
+    /// Redact values of properties whose name matches the glob case-insensitively, like '*TOKEN*'
+    #[arg(long, value_name = "GLOB", value_delimiter = ',')]
+    pub redact_property: Vec<glob::Pattern>,
+
+    /// Truncate each system-out and system-err to the bytes
+    #[arg(long, value_name = "BYTES")]
+    pub max_output_bytes: Option<usize>,
+
+    /// Truncate message and text of skipped, error, failure and reruns to the bytes
+    #[arg(long, value_name = "BYTES")]
+    pub max_detail_bytes: Option<usize>,
+
+    /// Which part of a truncated text is kept
+    #[arg(long, value_enum, default_value = "head")]
+    pub truncate_keep: TruncateKeep,
+
+    /// Strip ANSI escape sequences, or control characters too, from system-out, system-err and texts of skipped, error, failure and reruns
+    #[arg(long, value_enum, value_name = "MODE")]
+    pub strip: Option<StripMode>,
+
+    /// Add "metadata" of the key and value to the output. It can be repeated
+    #[arg(long, value_name = "KEY=VALUE", value_parser = parse_key_value)]
+    pub meta: Vec<(String, String)>,
+
+    /// Add "metadata" of the CI run like commit, branch, pipeline and job from environment variables of GitHub Actions, GitLab CI, CircleCI, Jenkins and Buildkite
+    #[arg(long, default_value = "false")]
+    pub meta_ci: bool,
+
+    /// Add "metadata" of commit, branch, author and commit time of the git repository of the directory, or the current directory
+    #[arg(long, value_name = "DIR", num_args = 0..=1, require_equals = true, default_missing_value = ".")]
+    pub git: Option<PathBuf>,
+
+    /// Resolve "file" of testsuites and testcases to the path relative to the root of the git repository of --git
+    #[arg(long, default_value = "false", requires = "git")]
+    pub git_relative_file: bool,
+
+    /// Recompute counters and time of testsuites from the kept testcases, or warn when they disagree
+    #[arg(long, value_enum, conflicts_with_all = ["stream", "flat"])]
+    pub counters: Option<CountersMode>,
+
+    /// Output JSON Lines of each testcase with its testsuites and testsuite, without loading the whole XML
+    #[arg(long, default_value = "false", conflicts_with_all = ["pretty", "lossless", "lenient"])]
+    pub stream: bool,
+
+    /// Output one flat JSON object per testcase per line with the fields of its testsuite, for `bq load --source_format=NEWLINE_DELIMITED_JSON`
+    #[arg(long, default_value = "false", conflicts_with_all = ["pretty", "lossless", "lenient", "stream"])]
+    pub flat: bool,
+}
+
+/// The path that means stdin.
+pub const STDIN: &str = "-";
+
+impl Args {
+    /// Returns the files of `paths`, expanding glob patterns and directories.
+    /// Stdin is returned as [`STDIN`].
+    pub fn input_files(&self) -> io::Result<Vec<PathBuf>> {
+        expand_paths(&self.path_args())
+    }
+
+    /// Returns the filter of testcases from `--only-*` and `--min-time`.
+    pub fn testcase_filter(&self) -> TestCaseFilter {
+        TestCaseFilter {
+            outcomes: self.only_outcome.iter().map(Outcome::from).collect(),
+            name: self.only_name.clone(),
+            classname: self.only_classname.clone(),
+            file: self.only_file.clone(),
+            min_time: self.min_time,
+        }
+    }
+
+    /// Returns the options of truncation from `--max-*-bytes`, or `None` without limits.
+    pub fn truncate_options(&self) -> Option<TruncateOptions> {
+        if self.max_output_bytes.is_none() && self.max_detail_bytes.is_none() {
+            return None;
+        }
+        Some(TruncateOptions {
+            max_output_bytes: self.max_output_bytes,
+            max_detail_bytes: self.max_detail_bytes,
+            keep: self.truncate_keep.clone(),
+        })
+    }
+
+    /// Returns the options of redaction from `--redact*`, or `None` without them.
+    pub fn redact_options(&self) -> Option<RedactOptions> {
+        if !self.redact && self.redact_pattern.is_empty() && self.redact_property.is_empty() {
+            return None;
+        }
+        Some(RedactOptions {
+            builtin: self.redact,
+            patterns: self.redact_pattern.clone(),
+            property_names: self.redact_property.clone(),
+        })
+    }
+
+    /// Returns the git information of `--git`, or `None` without it.
+    pub fn git_info(&self) -> io::Result<Option<GitInfo>> {
+        self.git.as_deref().map(GitInfo::discover).transpose()
+    }
+
+    /// Returns "metadata" from `--git`, `--meta-ci` and `--meta`.
+    /// `--meta-ci` overrides `--git` like the branch of a pull request, and `--meta` overrides both.
+    pub fn metadata(&self, git: Option<&GitInfo>) -> serde_json::Map<String, serde_json::Value> {
+        let mut metadata = git.map(GitInfo::to_metadata).unwrap_or_default();
+        if self.meta_ci {
+            metadata.extend(detect_ci_metadata(|name| std::env::var(name).ok()));
+        }
+        for (key, value) in &self.meta {
+            metadata.insert(key.clone(), value.clone().into());
+        }
+        metadata
+    }
+
+    /// Whether each JSON is tagged with "path" of its file.
+    /// The output of a single file is kept as it is for compatibility.
+    pub fn tags_path(&self, files: &[PathBuf]) -> bool {
+        let paths = self.path_args();
+        let single_file = paths.len() == 1 && files.len() == 1 && files[0] == Path::new(paths[0]);
+        !(single_file && matches!(self.output, OutputMode::Document))
+    }
+
+    /// `paths`, or stdin when they are omitted.
+    fn path_args(&self) -> Vec<&str> {
+        match self.paths.is_empty() {
+            true => vec![STDIN],
+            false => self.paths.iter().map(String::as_str).collect(),
+        }
+    }
+}
+
+/// Returns the files of the paths, expanding glob patterns and directories.
+fn expand_paths(paths: &[&str]) -> io::Result<Vec<PathBuf>> {
+    let mut files = vec![];
+    for path in paths {
+        if path.contains(['*', '?', '[']) {
+            let entries =
+                glob::glob(path).map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+            let count = files.len();
+            for entry in entries {
+                push_files(&entry.map_err(io::Error::from)?, &mut files)?;
+            }
+            if files.len() == count {
+                return Err(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("no files match {}", path),
+                ));
+            }
+        } else if *path == STDIN {
+            files.push(PathBuf::from(STDIN));
+        } else {
+            push_files(Path::new(path), &mut files)?;
+        }
+    }
+    Ok(files)
+}
+
+/// Push the path if it is a file, or `*.xml` files in it recursively if it is a directory.
+fn push_files(path: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    if !path.is_dir() {
+        files.push(path.to_path_buf());
+        return Ok(());
+    }
+    let mut entries = std::fs::read_dir(path)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<io::Result<Vec<_>>>()?;
+    entries.sort();
+    for entry in entries {
+        if entry.is_dir() {
+            push_files(&entry, files)?;
+        } else if entry
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("xml"))
+        {
+            files.push(entry);
+        }
+    }
+    Ok(())
+}
+
+/// Parse `KEY=VALUE` of `--meta`.
+fn parse_key_value(s: &str) -> Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Ok((key.to_string(), value.to_string())),
+        _ => Err(format!("expected KEY=VALUE, but got {}", s)),
+    }
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Output BigQuery table schema or JSON Schema of the JSON
+    Schema(SchemaArgs),
+    /// Convert the JSON of junit2json back to JUnit XML
+    ToXml(ToXmlArgs),
+    /// Merge many JUnit XML reports into one testsuites
+    Merge(MergeArgs),
+}
+
+/// Format of the schema that `schema` subcommand outputs.
+#[derive(Clone, ValueEnum, Debug, Default)]
+pub enum SchemaFormat {
+    #[default]
+    Bigquery,
+    /// JSON Schema draft 2020-12
+    JsonSchema,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct SchemaArgs {
+    /// Format of the schema
+    #[arg(long, value_enum, default_value = "bigquery")]
+    pub format: SchemaFormat,
+
+    /// Schema of the JSON Lines of --flat instead of the JSON document
+    #[arg(long, default_value = "false")]
+    pub flat: bool,
+
+    /// JSON shape of skipped, error and failure in testcase
+    #[arg(long, value_enum, default_value = "object")]
+    pub detail_shape: DetailShape,
+
+    /// Add "metadata" of --meta and --meta-ci
+    #[arg(long, default_value = "false")]
+    pub metadata: bool,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct ToXmlArgs {
+    /// JSON path. Read from stdin when it is omitted or "-"
+    pub path: Option<String>,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct MergeArgs {
+    /// JUnit XML paths, glob patterns or directories to merge. "-" reads from stdin
+    #[arg(required = true)]
+    pub paths: Vec<String>,
+
+    /// Name of the merged testsuites
+    #[arg(long)]
+    pub name: Option<String>,
+
+    /// Merge testsuites with the same name into one
+    #[arg(long, default_value = "false")]
+    pub merge_same_name: bool,
+
+    /// Output JUnit XML instead of JSON
+    #[arg(long, default_value = "false", conflicts_with_all = ["pretty", "detail_shape"])]
+    pub xml: bool,
+
+    /// Output pretty JSON
+    #[arg(short, long, default_value = "false")]
+    pub pretty: bool,
+
+    /// JSON shape of skipped, error and failure in testcase
+    #[arg(long, value_enum, default_value = "object")]
+    pub detail_shape: DetailShape,
+
+    /// Keep unrecognized XML attributes and tags in "extra"
+    #[arg(long, default_value = "false")]
+    pub lossless: bool,
+}
+
+impl MergeArgs {
+    /// Returns the files of `paths`, expanding glob patterns and directories.
+    pub fn input_files(&self) -> io::Result<Vec<PathBuf>> {
+        expand_paths(&self.paths.iter().map(String::as_str).collect::<Vec<_>>())
+    }
 }
@@ -0,0 +1,318 @@
+//! Recompute and validate `tests`, `failures`, `errors`, `skipped`, `disabled` and `time`
+//! of `<testsuites>` and `<testsuite>` from their testcases.
+//!
+//! `time` of a suite is its wall time, that includes setup and is shorter than the sum of its testcases
+//! when they run in parallel. So it is validated only against the longest testcase or nested testsuite,
+//! which it can never be shorter than.
+
+use crate::merge::add_time;
+use crate::{Outcome, TestCase, TestSuite, TestSuites, TestSuitesOrTestSuite};
+use std::fmt;
+
+/// A counter of `<testsuites>` or `<testsuite>` that disagrees with its testcases.
+/// See [`TestSuitesOrTestSuite::validate_counters`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct CounterMismatch {
+    /// Element path like `testsuites/testsuite[3]`
+    pub path: String,
+    /// Name of the counter like `failures`
+    pub counter: &'static str,
+    /// The value in the XML
+    pub declared: f64,
+    /// The value counted from the testcases, or the longest testcase or nested testsuite for `time`
+    pub actual: f64,
+}
+impl fmt::Display for CounterMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.counter {
+            "time" => write!(
+                f,
+                "time=\"{}\" but a testcase or testsuite takes {} ({})",
+                self.declared, self.actual, self.path
+            ),
+            _ => write!(
+                f,
+                "{}=\"{}\" but {} are counted from testcases ({})",
+                self.counter, self.declared, self.actual, self.path
+            ),
+        }
+    }
+}
+
+/// Declared times are rounded, like to milliseconds, so a shorter `time` within this is not reported.
+const TIME_TOLERANCE: f32 = 0.001;
+
+impl TestSuitesOrTestSuite {
+    /// Overwrite `tests`, `failures`, `errors`, `skipped` and `disabled` of every `<testsuites>` and `<testsuite>`
+    /// with the numbers counted from their testcases, and `time` with the sum of their times.
+    ///
+    /// Each testcase is counted by [`crate::TestCase::outcome`].
+    /// [`Outcome::Disabled`] is counted in `disabled` of `<testsuite>`, not in `skipped`,
+    /// and `disabled` is only written when it is declared or not zero.
+    /// `time` is kept when no testcase has it, otherwise the wall time of parallel testcases
+    /// is replaced by the sum of the kept ones.
+    ///
+    /// # Examples
+    /// ```
+    /// use junit2json;
+    ///
+    /// let xml = r#"
+    ///   <testsuite name="suite1" tests="1">
+    ///       <testcase name="case1" time="0.1" />
+    ///       <testcase name="case2" time="0.2"><failure /></testcase>
+    ///   </testsuite>
+    /// "#;
+    /// let mut testsuite = junit2json::from_str(xml).unwrap();
+    /// testsuite.recompute_counters();
+    /// assert!(testsuite.validate_counters().is_empty());
+    /// ```
+    pub fn recompute_counters(&mut self) {
+        match self {
+            TestSuitesOrTestSuite::TestSuites(testsuites) => {
+                recompute_testsuites(testsuites);
+            }
+            TestSuitesOrTestSuite::TestSuite(testsuite) => {
+                recompute_testsuite(testsuite);
+            }
+        }
+    }
+
+    /// Returns every counter of `<testsuites>` and `<testsuite>` that disagrees with their testcases.
+    /// Absent counters are not reported. See [`TestSuitesOrTestSuite::recompute_counters`] for how they are counted.
+    ///
+    /// `time` is reported only when it is shorter than a testcase or nested testsuite,
+    /// because it is longer or shorter than the sum of them with setup or parallel testcases.
+    ///
+    /// # Examples
+    /// ```
+    /// use junit2json;
+    ///
+    /// let xml = r#"
+    ///   <testsuite name="suite1" tests="3" failures="0">
+    ///       <testcase name="case1" />
+    ///       <testcase name="case2"><failure /></testcase>
+    ///   </testsuite>
+    /// "#;
+    /// let testsuite = junit2json::from_str(xml).unwrap();
+    /// for mismatch in testsuite.validate_counters() {
+    ///     eprintln!("warning: {}", mismatch);
+    /// }
+    /// ```
+    pub fn validate_counters(&self) -> Vec<CounterMismatch> {
+        let mut mismatches = vec![];
+        match self {
+            TestSuitesOrTestSuite::TestSuites(testsuites) => {
+                validate_testsuites(testsuites, &mut mismatches);
+            }
+            TestSuitesOrTestSuite::TestSuite(testsuite) => {
+                validate_testsuite(testsuite, "testsuite".to_string(), &mut mismatches);
+            }
+        }
+        mismatches
+    }
+}
+
+/// Numbers counted from testcases.
+#[derive(Default)]
+struct Counts {
+    tests: u32,
+    failures: u32,
+    errors: u32,
+    skipped: u32,
+    disabled: u32,
+    time: Option<f32>,
+    /// The longest testcase or nested testsuite, that the suite can not be shorter than
+    longest: Option<f32>,
+}
+impl Counts {
+    fn of_testcases(testcases: &[TestCase]) -> Self {
+        let mut counts = Counts::default();
+        for testcase in testcases {
+            counts.tests += 1;
+            match testcase.outcome() {
+                Outcome::Failed => counts.failures += 1,
+                Outcome::Errored => counts.errors += 1,
+                Outcome::Skipped => counts.skipped += 1,
+                Outcome::Disabled => counts.disabled += 1,
+                Outcome::Passed | Outcome::Flaky => {}
+            }
+            counts.time = add_time(counts.time, testcase.time);
+            counts.longest = longer(counts.longest, testcase.time);
+        }
+        counts
+    }
+    /// Add the counts of a child suite, whose `time` is its own if it has one.
+    fn add(&mut self, other: Counts, time: Option<f32>) {
+        self.tests += other.tests;
+        self.failures += other.failures;
+        self.errors += other.errors;
+        self.skipped += other.skipped;
+        self.disabled += other.disabled;
+        self.time = add_time(self.time, time.or(other.time));
+        self.longest = longer(self.longest, time.or(other.longest));
+    }
+    fn pairs(&self) -> [(&'static str, u32); 4] {
+        [
+            ("tests", self.tests),
+            ("failures", self.failures),
+            ("errors", self.errors),
+            ("skipped", self.skipped),
+        ]
+    }
+}
+
+fn recompute_testsuites(testsuites: &mut TestSuites) {
+    let mut counts = Counts::default();
+    for testsuite in testsuites.testsuite.iter_mut().flatten() {
+        let child = recompute_testsuite(testsuite);
+        counts.add(child, testsuite.time);
+    }
+    testsuites.tests = Some(counts.tests);
+    testsuites.failures = Some(counts.failures);
+    testsuites.errors = Some(counts.errors);
+    testsuites.skipped = Some(counts.skipped);
+    testsuites.time = counts.time.or(testsuites.time);
+}
+
+fn recompute_testsuite(testsuite: &mut TestSuite) -> Counts {
+    let mut counts = Counts::of_testcases(testsuite.testcase.as_deref().unwrap_or_default());
+    for nested in testsuite.testsuite.iter_mut().flatten() {
+        let child = recompute_testsuite(nested);
+        counts.add(child, nested.time);
+    }
+    testsuite.tests = Some(counts.tests);
+    testsuite.failures = Some(counts.failures);
+    testsuite.errors = Some(counts.errors);
+    testsuite.skipped = Some(counts.skipped);
+    if testsuite.disabled.is_some() || counts.disabled > 0 {
+        testsuite.disabled = Some(counts.disabled);
+    }
+    testsuite.time = counts.time.or(testsuite.time);
+    counts
+}
+
+fn longer(a: Option<f32>, b: Option<f32>) -> Option<f32> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.max(b)),
+        _ => a.or(b),
+    }
+}
+
+/// Convert `f32` through its shortest representation, so `0.1` does not become `0.10000000149011612`.
+fn to_f64(value: f32) -> f64 {
+    value.to_string().parse().unwrap_or(value.into())
+}
+
+/// Recompute the counters of `testsuite` when it lacks `tests` or a counter disagrees with its testcases.
+/// Returns whether they are recomputed.
+///
+/// An absent `failures`, `errors` or `skipped` agrees with no such testcase,
+/// and a testsuite without testcases, like a summary of a shard, keeps its counters.
+pub(crate) fn recount_inconsistent(testsuite: &mut TestSuite) -> bool {
+    let counts = validate_testsuite(testsuite, String::new(), &mut vec![]);
+    let declared = [
+        testsuite.tests,
+        testsuite.failures,
+        testsuite.errors,
+        testsuite.skipped,
+    ];
+    let consistent = counts.tests == 0
+        || declared.iter().zip(counts.pairs()).all(
+            |(declared, (counter, actual))| match declared {
+                Some(declared) => *declared == actual,
+                None => counter != "tests" && actual == 0,
+            },
+        );
+    if !consistent {
+        recompute_testsuite(testsuite);
+    }
+    !consistent
+}
+
+fn validate_testsuites(testsuites: &TestSuites, mismatches: &mut Vec<CounterMismatch>) {
+    let start = mismatches.len();
+    let mut counts = Counts::default();
+    for (index, testsuite) in testsuites.testsuite.iter().flatten().enumerate() {
+        let path = format!("testsuites/testsuite[{}]", index + 1);
+        let child = validate_testsuite(testsuite, path, mismatches);
+        counts.add(child, testsuite.time);
+    }
+    let declared = [
+        testsuites.tests,
+        testsuites.failures,
+        testsuites.errors,
+        testsuites.skipped,
+        None,
+    ];
+    push_mismatches(
+        "testsuites",
+        &declared,
+        testsuites.time,
+        &counts,
+        mismatches,
+        start,
+    );
+}
+
+fn validate_testsuite(
+    testsuite: &TestSuite,
+    path: String,
+    mismatches: &mut Vec<CounterMismatch>,
+) -> Counts {
+    let start = mismatches.len();
+    let mut counts = Counts::of_testcases(testsuite.testcase.as_deref().unwrap_or_default());
+    for (index, nested) in testsuite.testsuite.iter().flatten().enumerate() {
+        let nested_path = format!("{}/testsuite[{}]", path, index + 1);
+        let child = validate_testsuite(nested, nested_path, mismatches);
+        counts.add(child, nested.time);
+    }
+    let declared = [
+        testsuite.tests,
+        testsuite.failures,
+        testsuite.errors,
+        testsuite.skipped,
+        testsuite.disabled,
+    ];
+    push_mismatches(&path, &declared, testsuite.time, &counts, mismatches, start);
+    counts
+}
+
+/// Insert the mismatches of a suite at `index`, before the ones of its children.
+/// `declared` is `tests`, `failures`, `errors`, `skipped` and `disabled`.
+fn push_mismatches(
+    path: &str,
+    declared: &[Option<u32>; 5],
+    time: Option<f32>,
+    counts: &Counts,
+    mismatches: &mut Vec<CounterMismatch>,
+    index: usize,
+) {
+    let pairs = counts
+        .pairs()
+        .into_iter()
+        .chain([("disabled", counts.disabled)]);
+    let mut found = declared
+        .iter()
+        .zip(pairs)
+        .filter_map(|(declared, (counter, actual))| match declared {
+            Some(declared) if *declared != actual => Some(CounterMismatch {
+                path: path.to_string(),
+                counter,
+                declared: (*declared).into(),
+                actual: actual.into(),
+            }),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    if let (Some(time), Some(longest)) = (time, counts.longest) {
+        if time + TIME_TOLERANCE < longest {
+            found.push(CounterMismatch {
+                path: path.to_string(),
+                counter: "time",
+                declared: to_f64(time),
+                actual: to_f64(longest),
+            });
+        }
+    }
+    mismatches.splice(index..index, found);
+}
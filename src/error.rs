@@ -0,0 +1,594 @@
+//! Error type of junit2json.
+//!
+//! serde does not tell where the problem is, so when deserializing a string fails,
+//! the XML is walked again to find the position and the element path of the problem.
+//! A reader can not be walked again, so its XML is walked with the element path while it is deserialized.
+
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::{DeError, Reader, Writer};
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, BufRead, Read};
+
+/// Where the problem is in the JUnit XML.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Location {
+    /// Byte offset from the beginning of the XML
+    pub offset: u64,
+    /// 1-based line number
+    pub line: usize,
+    /// 1-based column number, counted in characters
+    pub column: usize,
+    /// Element path like `testsuites/testsuite[3]/testcase[17]/@time`
+    pub path: String,
+}
+impl Location {
+    pub(crate) fn new(xml: &str, offset: u64, path: String) -> Self {
+        let mut end = (offset as usize).min(xml.len());
+        while !xml.is_char_boundary(end) {
+            end -= 1;
+        }
+        let before = &xml[..end];
+        let line = before.matches('\n').count() + 1;
+        let column = match before.rfind('\n') {
+            Some(newline) => before[newline + 1..].chars().count() + 1,
+            None => before.chars().count() + 1,
+        };
+        Location {
+            offset,
+            line,
+            column,
+            path,
+        }
+    }
+    /// Convert a location in an XML fragment to the location in the whole XML,
+    /// where the fragment starts at `start`.
+    pub(crate) fn shift(&mut self, start: &Location) {
+        if self.line == 1 {
+            self.column += start.column - 1;
+        }
+        self.line += start.line - 1;
+        self.offset += start.offset;
+    }
+}
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}, column {}", self.line, self.column)?;
+        if !self.path.is_empty() {
+            write!(f, " ({})", self.path)?;
+        }
+        Ok(())
+    }
+}
+
+/// A problem that is recovered in the lenient mode. See [`crate::from_str_lenient`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Warning {
+    pub message: String,
+    pub location: Location,
+}
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at {}", self.message, self.location)
+    }
+}
+
+/// Error of [`crate::from_reader`], [`crate::from_str`] and their variants.
+#[derive(Debug)]
+pub enum Error {
+    /// Failed to read the input
+    Io(io::Error),
+    /// The input is not well-formed XML
+    Syntax { message: String, location: Location },
+    /// A numeric attribute like `time` or `tests` has a value that is not a number
+    InvalidNumber {
+        value: String,
+        message: String,
+        location: Location,
+    },
+    /// The root element is neither `<testsuites>` nor `<testsuite>`
+    UnexpectedRoot { name: String, location: Location },
+    /// Any other problem found while deserializing
+    Deserialize { message: String, location: Location },
+}
+impl Error {
+    /// Returns where the problem is. `None` for [`Error::Io`].
+    pub fn location(&self) -> Option<&Location> {
+        match self {
+            Error::Io(_) => None,
+            Error::Syntax { location, .. }
+            | Error::InvalidNumber { location, .. }
+            | Error::UnexpectedRoot { location, .. }
+            | Error::Deserialize { location, .. } => Some(location),
+        }
+    }
+    pub(crate) fn location_mut(&mut self) -> Option<&mut Location> {
+        match self {
+            Error::Io(_) => None,
+            Error::Syntax { location, .. }
+            | Error::InvalidNumber { location, .. }
+            | Error::UnexpectedRoot { location, .. }
+            | Error::Deserialize { location, .. } => Some(location),
+        }
+    }
+}
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io(err) => write!(f, "IO error: {}", err),
+            Error::Syntax { message, location } => {
+                write!(f, "XML syntax error at {}: {}", location, message)
+            }
+            Error::InvalidNumber {
+                value,
+                message,
+                location,
+            } => write!(f, "invalid number {:?} at {}: {}", value, location, message),
+            Error::UnexpectedRoot { name, location } => write!(
+                f,
+                "unexpected root element <{}> at {}: expected <testsuites> or <testsuite>",
+                name, location
+            ),
+            Error::Deserialize { message, location } => {
+                write!(f, "invalid JUnit XML at {}: {}", location, message)
+            }
+        }
+    }
+}
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+/// Tracks the element path like `testsuites/testsuite[3]/testcase[17]` while walking XML events.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct ElementPath {
+    /// Name, path segment and the number of children for each name
+    stack: Vec<(String, String, HashMap<String, usize>)>,
+}
+impl ElementPath {
+    pub(crate) fn push(&mut self, name: &str) {
+        let segment = match self.stack.last_mut() {
+            Some((_, _, children)) => {
+                let count = children.entry(name.to_string()).or_insert(0);
+                *count += 1;
+                format!("{}[{}]", name, count)
+            }
+            None => name.to_string(),
+        };
+        self.stack.push((name.to_string(), segment, HashMap::new()));
+    }
+    pub(crate) fn pop(&mut self) {
+        self.stack.pop();
+    }
+    pub(crate) fn is_empty(&self) -> bool {
+        self.stack.is_empty()
+    }
+    pub(crate) fn parent_name(&self) -> Option<&str> {
+        self.stack
+            .len()
+            .checked_sub(2)
+            .map(|i| self.stack[i].0.as_str())
+    }
+    pub(crate) fn with_attribute(&self, attr: &str) -> String {
+        format!("{}/@{}", self, attr)
+    }
+}
+impl fmt::Display for ElementPath {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let segments = self
+            .stack
+            .iter()
+            .map(|(_, segment, _)| segment.as_str())
+            .collect::<Vec<_>>();
+        f.write_str(&segments.join("/"))
+    }
+}
+
+/// An attribute that can not be deserialized into the corresponding struct field.
+pub(crate) struct InvalidAttribute {
+    pub(crate) name: String,
+    pub(crate) value: String,
+    pub(crate) message: String,
+}
+
+/// Counts lines and columns of the bytes consumed by quick-xml.
+pub(crate) struct PositionReader<R> {
+    inner: R,
+    consumed: u64,
+    line: usize,
+    column: usize,
+    line_start: u64,
+}
+impl<R> PositionReader<R> {
+    pub(crate) fn new(inner: R) -> Self {
+        PositionReader {
+            inner,
+            consumed: 0,
+            line: 1,
+            column: 1,
+            line_start: 0,
+        }
+    }
+
+    /// Returns the line and column of `offset`.
+    ///
+    /// quick-xml can consume a little more than the position it reports, like `<` of the next tag,
+    /// so go back from the current position as long as `offset` is in the current line.
+    fn line_column(&self, offset: u64) -> (usize, usize) {
+        match offset >= self.line_start && offset <= self.consumed {
+            true => (
+                self.line,
+                self.column
+                    .saturating_sub((self.consumed - offset) as usize)
+                    .max(1),
+            ),
+            false => (self.line, self.column),
+        }
+    }
+
+    /// Returns the location of `offset` with the element path.
+    pub(crate) fn location(&self, offset: u64, path: String) -> Location {
+        let (line, column) = self.line_column(offset);
+        Location {
+            offset,
+            line,
+            column,
+            path,
+        }
+    }
+}
+impl<R: BufRead> Read for PositionReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = {
+            let available = self.fill_buf()?;
+            let len = available.len().min(buf.len());
+            buf[..len].copy_from_slice(&available[..len]);
+            len
+        };
+        self.consume(len);
+        Ok(len)
+    }
+}
+impl<R: BufRead> BufRead for PositionReader<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.inner.fill_buf()
+    }
+    fn consume(&mut self, amt: usize) {
+        if let Ok(available) = self.inner.fill_buf() {
+            for byte in &available[..amt.min(available.len())] {
+                self.consumed += 1;
+                if *byte == b'\n' {
+                    self.line += 1;
+                    self.column = 1;
+                    self.line_start = self.consumed;
+                } else if byte & 0xC0 != 0x80 {
+                    // Count only the first byte of each UTF-8 character
+                    self.column += 1;
+                }
+            }
+        }
+        self.inner.consume(amt);
+    }
+}
+
+/// Walks the XML events with the element path while the deserializer reads them.
+///
+/// The root and the numeric attributes are checked like [`locate`] before each event is handed to the deserializer,
+/// so that the reader API returns the same errors as the string API without keeping the XML.
+pub(crate) struct CheckedReader<R> {
+    reader: Reader<PositionReader<R>>,
+    buf: Vec<u8>,
+    /// Bytes of the current event that the deserializer has not consumed yet
+    event: Vec<u8>,
+    consumed: usize,
+    path: ElementPath,
+    /// Offset of the current event
+    offset: u64,
+    /// The problem found while walking, that is returned instead of the one serde reports
+    error: Cell<Option<Error>>,
+}
+impl<R: BufRead> CheckedReader<R> {
+    pub(crate) fn new(inner: R) -> Self {
+        CheckedReader {
+            reader: Reader::from_reader(PositionReader::new(inner)),
+            buf: vec![],
+            event: vec![],
+            consumed: 0,
+            path: ElementPath::default(),
+            offset: 0,
+            error: Cell::new(None),
+        }
+    }
+
+    fn location(&self, offset: u64, path: String) -> Location {
+        self.reader.get_ref().location(offset, path)
+    }
+
+    fn xml_error(&self, err: quick_xml::Error, offset: u64) -> Error {
+        match err {
+            quick_xml::Error::Io(err) => Error::Io(io::Error::new(err.kind(), err.to_string())),
+            err => Error::Syntax {
+                message: err.to_string(),
+                location: self.location(offset, self.path.to_string()),
+            },
+        }
+    }
+
+    /// Returns the problem found while walking, or the one that serde reported around the current event.
+    pub(crate) fn error(&self, err: DeError) -> Error {
+        match self.error.take() {
+            Some(error) => error,
+            None => from_de_error(err, self.location(self.offset, self.path.to_string())),
+        }
+    }
+
+    /// Read the next event into `self.event`. Returns `false` at the end of the XML.
+    fn read_event(&mut self) -> Result<bool, Error> {
+        let offset = self.reader.buffer_position();
+        self.buf.clear();
+        let event = match self.reader.read_event_into(&mut self.buf) {
+            Ok(event) => event,
+            Err(err) => return Err(self.xml_error(err, self.reader.error_position())),
+        };
+        self.offset = offset;
+        match event {
+            Event::Start(ref start) | Event::Empty(ref start) => {
+                let name = String::from_utf8_lossy(start.name().as_ref()).into_owned();
+                let is_root = self.path.is_empty();
+                self.path.push(&name);
+                if is_root && name != "testsuites" && name != "testsuite" {
+                    return Err(Error::UnexpectedRoot {
+                        name,
+                        location: self.location(offset, self.path.to_string()),
+                    });
+                }
+                match check_attributes(&self.path, start).map(|invalid| invalid.into_iter().next())
+                {
+                    Ok(Some(attr)) => {
+                        return Err(Error::InvalidNumber {
+                            value: attr.value,
+                            message: attr.message,
+                            location: self.location(offset, self.path.with_attribute(&attr.name)),
+                        })
+                    }
+                    Ok(None) => {}
+                    Err(err) => return Err(self.xml_error(err, offset)),
+                }
+            }
+            Event::Eof => return Ok(false),
+            _ => {}
+        }
+        let pop = matches!(event, Event::Empty(_) | Event::End(_));
+        self.event.clear();
+        Writer::new(&mut self.event).write_event(event)?;
+        if pop {
+            self.path.pop();
+        }
+        Ok(true)
+    }
+}
+impl<R: BufRead> Read for CheckedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = {
+            let available = self.fill_buf()?;
+            let len = available.len().min(buf.len());
+            buf[..len].copy_from_slice(&available[..len]);
+            len
+        };
+        self.consume(len);
+        Ok(len)
+    }
+}
+impl<R: BufRead> BufRead for CheckedReader<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        while self.consumed == self.event.len() {
+            self.event.clear();
+            self.consumed = 0;
+            match self.read_event() {
+                Ok(true) => {}
+                Ok(false) => break,
+                Err(err) => {
+                    let message = err.to_string();
+                    self.error.set(Some(err));
+                    return Err(io::Error::new(io::ErrorKind::InvalidData, message));
+                }
+            }
+        }
+        Ok(&self.event[self.consumed..])
+    }
+    fn consume(&mut self, amt: usize) {
+        self.consumed = (self.consumed + amt).min(self.event.len());
+    }
+}
+
+/// Type of a numeric attribute, that is `u32` or `f32` in the structs.
+#[derive(Clone, Copy)]
+enum Number {
+    Unsigned,
+    Float,
+}
+impl Number {
+    /// Parse the value like the deserializer does, that is `str::parse` of the raw value.
+    fn check(self, value: &str) -> Result<(), String> {
+        match self {
+            Number::Unsigned => value
+                .parse::<u32>()
+                .map(|_| ())
+                .map_err(|err| format!("expected u32: {}", err)),
+            Number::Float => value
+                .parse::<f32>()
+                .map(|_| ())
+                .map_err(|err| format!("expected f32: {}", err)),
+        }
+    }
+}
+
+/// Numeric attributes of the elements that are known by the structs.
+fn numeric_attributes(path: &ElementPath, name: &[u8]) -> &'static [(&'static str, Number)] {
+    use Number::{Float, Unsigned};
+    match (path.parent_name(), name) {
+        (None, b"testsuites") => &[
+            ("time", Float),
+            ("tests", Unsigned),
+            ("failures", Unsigned),
+            ("errors", Unsigned),
+            ("skipped", Unsigned),
+            ("assertions", Unsigned),
+        ],
+        (None | Some("testsuites" | "testsuite"), b"testsuite") => &[
+            ("tests", Unsigned),
+            ("failures", Unsigned),
+            ("errors", Unsigned),
+            ("time", Float),
+            ("disabled", Unsigned),
+            ("skipped", Unsigned),
+        ],
+        (Some("testsuite"), b"testcase") => &[
+            ("assertions", Unsigned),
+            ("time", Float),
+            ("line", Unsigned),
+        ],
+        (Some("testcase"), b"flakyFailure" | b"flakyError" | b"rerunFailure" | b"rerunError") => {
+            &[("time", Float)]
+        }
+        _ => &[],
+    }
+}
+
+/// Find the numeric attributes of the element that can not be deserialized.
+pub(crate) fn check_attributes(
+    path: &ElementPath,
+    start: &BytesStart,
+) -> Result<Vec<InvalidAttribute>, quick_xml::Error> {
+    let numbers = numeric_attributes(path, start.name().as_ref());
+    let mut invalid = vec![];
+    if numbers.is_empty() {
+        return Ok(invalid);
+    }
+    for attr in start.attributes() {
+        let attr = attr?;
+        let Some((name, number)) = numbers
+            .iter()
+            .find(|(name, _)| name.as_bytes() == attr.key.as_ref())
+        else {
+            continue;
+        };
+        // The deserializer parses the value without unescaping it
+        if let Err(message) = number.check(&String::from_utf8_lossy(&attr.value)) {
+            invalid.push(InvalidAttribute {
+                name: name.to_string(),
+                value: attr.unescape_value()?.into_owned(),
+                message,
+            });
+        }
+    }
+    Ok(invalid)
+}
+
+pub(crate) fn from_xml_error(
+    xml: &str,
+    err: quick_xml::Error,
+    position: u64,
+    path: String,
+) -> Error {
+    match err {
+        quick_xml::Error::Io(err) => Error::Io(io::Error::new(err.kind(), err.to_string())),
+        err => Error::Syntax {
+            message: err.to_string(),
+            location: Location::new(xml, position, path),
+        },
+    }
+}
+
+/// Convert the error that serde reported at `location` without walking the XML, like the one of a reader.
+pub(crate) fn from_de_error(err: DeError, location: Location) -> Error {
+    match err {
+        DeError::InvalidXml(quick_xml::Error::Io(err)) => {
+            Error::Io(io::Error::new(err.kind(), err.to_string()))
+        }
+        DeError::InvalidXml(err @ quick_xml::Error::Encoding(_)) => {
+            Error::Io(io::Error::new(io::ErrorKind::InvalidData, err.to_string()))
+        }
+        DeError::InvalidXml(err) => Error::Syntax {
+            message: err.to_string(),
+            location,
+        },
+        err => Error::Deserialize {
+            message: err.to_string(),
+            location,
+        },
+    }
+}
+
+/// Walk the XML to find the location of the error that serde reported.
+///
+/// `position` is the position of the deserializer when the error happened.
+/// It is used when the walk can not find the problem by itself.
+pub(crate) fn locate(xml: &str, err: DeError, position: u64) -> Error {
+    locate_in(xml, err, position, ElementPath::default())
+}
+
+/// Same as [`locate`], but `xml` is a fragment like `<testcase>` whose parents are `path`.
+pub(crate) fn locate_in(xml: &str, err: DeError, position: u64, mut path: ElementPath) -> Error {
+    let mut reader = Reader::from_str(xml);
+    let mut path_at_position = None;
+    loop {
+        let offset = reader.buffer_position();
+        if path_at_position.is_none() && offset >= position {
+            path_at_position = Some(path.to_string());
+        }
+        let event = match reader.read_event() {
+            Ok(event) => event,
+            Err(err) => return from_xml_error(xml, err, reader.error_position(), path.to_string()),
+        };
+        match event {
+            Event::Start(ref start) | Event::Empty(ref start) => {
+                let name = String::from_utf8_lossy(start.name().as_ref()).into_owned();
+                let is_root = path.is_empty();
+                path.push(&name);
+                if is_root && name != "testsuites" && name != "testsuite" {
+                    return Error::UnexpectedRoot {
+                        name,
+                        location: Location::new(xml, offset, path.to_string()),
+                    };
+                }
+                match check_attributes(&path, start).map(|invalid| invalid.into_iter().next()) {
+                    Ok(Some(attr)) => {
+                        return Error::InvalidNumber {
+                            value: attr.value,
+                            message: attr.message,
+                            location: Location::new(xml, offset, path.with_attribute(&attr.name)),
+                        }
+                    }
+                    Ok(None) => {}
+                    Err(err) => return from_xml_error(xml, err, offset, path.to_string()),
+                }
+                if let Event::Empty(_) = event {
+                    path.pop();
+                }
+            }
+            Event::End(_) => path.pop(),
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    let path = path_at_position.unwrap_or_default();
+    match err {
+        DeError::InvalidXml(err) => from_xml_error(xml, err, position, path),
+        err => Error::Deserialize {
+            message: err.to_string(),
+            location: Location::new(xml, position, path),
+        },
+    }
+}
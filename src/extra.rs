@@ -0,0 +1,334 @@
+//! Collect unrecognized attributes and child elements for the lossless mode.
+//!
+//! serde silently ignores everything that is not a field of the structs,
+//! so this module walks the XML events once more and keeps what serde dropped.
+//! Attributes are stored with `@` prefix as string and child elements are stored as array,
+//! because the number of them is unknown.
+
+use crate::error::{self, Error};
+use crate::{Detail, TestCase, TestSuite, TestSuites, TestSuitesOrTestSuite};
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use serde::de::{self, Deserialize, Visitor};
+use serde_json::{Map, Value};
+use std::cell::Cell;
+use std::collections::HashMap;
+
+/// A deserializer that only records the field names passed to `deserialize_struct`.
+struct FieldNames<'a>(&'a Cell<&'static [&'static str]>);
+
+impl<'de> de::Deserializer<'de> for FieldNames<'_> {
+    type Error = de::value::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Self::Error> {
+        Err(de::Error::custom("only struct is supported"))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.0.set(fields);
+        Err(de::Error::custom("field names are recorded"))
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map enum identifier ignored_any
+    }
+}
+
+/// Returns the field names of a struct as serde sees them when deserializing,
+/// so the known attributes and elements never drift from the struct definitions.
+pub(crate) fn field_names<'de, T: Deserialize<'de>>() -> &'static [&'static str] {
+    let fields = Cell::new(&[][..]);
+    let _ = T::deserialize(FieldNames(&fields));
+    fields.get()
+}
+
+/// Unrecognized items of one element and the nodes of its child structs.
+#[derive(Debug, Default)]
+pub(crate) struct ExtraNode {
+    extra: Map<String, Value>,
+    children: HashMap<String, Vec<ExtraNode>>,
+}
+impl ExtraNode {
+    fn take_extra(&mut self) -> Option<Map<String, Value>> {
+        match self.extra.is_empty() {
+            true => None,
+            false => Some(std::mem::take(&mut self.extra)),
+        }
+    }
+    fn take_children(&mut self, name: &str) -> Vec<ExtraNode> {
+        self.children.remove(name).unwrap_or_default()
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Kind {
+    TestSuites,
+    TestSuite,
+    TestCase,
+    Detail,
+}
+impl Kind {
+    fn fields(&self) -> &'static [&'static str] {
+        match self {
+            Kind::TestSuites => field_names::<TestSuites>(),
+            Kind::TestSuite => field_names::<TestSuite>(),
+            Kind::TestCase => field_names::<TestCase>(),
+            Kind::Detail => field_names::<Detail>(),
+        }
+    }
+    /// Child element that also has `extra`
+    fn child(&self, name: &str) -> Option<Kind> {
+        match (self, name) {
+            (Kind::TestSuites, "testsuite") => Some(Kind::TestSuite),
+            (Kind::TestSuite, "testsuite") => Some(Kind::TestSuite),
+            (Kind::TestSuite, "testcase") => Some(Kind::TestCase),
+            (Kind::TestCase, "skipped" | "error" | "failure") => Some(Kind::Detail),
+            _ => None,
+        }
+    }
+}
+
+/// Element that is not a field of the structs. It is converted to a JSON value.
+struct UnknownElement {
+    name: String,
+    value: Map<String, Value>,
+    text: String,
+}
+impl UnknownElement {
+    fn new(name: String) -> Self {
+        UnknownElement {
+            name,
+            value: Map::new(),
+            text: String::new(),
+        }
+    }
+    fn into_value(mut self) -> Value {
+        if self.value.is_empty() {
+            return Value::String(self.text);
+        }
+        if !self.text.is_empty() {
+            self.value
+                .insert("$text".to_string(), Value::String(self.text));
+        }
+        Value::Object(self.value)
+    }
+}
+
+enum Frame {
+    Known(Kind, String, ExtraNode),
+    /// Known element that does not have `extra`, like `<system-out>` or `<properties>`
+    Ignored,
+    Unknown(UnknownElement),
+}
+
+fn push_element(map: &mut Map<String, Value>, name: String, value: Value) {
+    // Attribute keys have `@` prefix, so an element key is always an array
+    if let Value::Array(values) = map.entry(name).or_insert_with(|| Value::Array(vec![])) {
+        values.push(value);
+    }
+}
+
+fn push_attributes(
+    start: &BytesStart,
+    known: &[&str],
+    map: &mut Map<String, Value>,
+) -> Result<(), quick_xml::Error> {
+    for attr in start.attributes() {
+        let attr = attr?;
+        let key = format!("@{}", String::from_utf8_lossy(attr.key.as_ref()));
+        if !known.contains(&key.as_str()) {
+            map.insert(key, Value::String(attr.unescape_value()?.into_owned()));
+        }
+    }
+    Ok(())
+}
+
+fn start_frame(stack: &[Frame], start: &BytesStart) -> Result<Frame, quick_xml::Error> {
+    let name = String::from_utf8_lossy(start.name().as_ref()).into_owned();
+    let mut frame = match stack.last() {
+        None => match name.as_str() {
+            "testsuites" => Frame::Known(Kind::TestSuites, name, ExtraNode::default()),
+            "testsuite" => Frame::Known(Kind::TestSuite, name, ExtraNode::default()),
+            _ => Frame::Ignored,
+        },
+        Some(Frame::Known(kind, _, _)) => match kind.child(&name) {
+            Some(child) => Frame::Known(child, name, ExtraNode::default()),
+            None if kind.fields().contains(&name.as_str()) => Frame::Ignored,
+            None => Frame::Unknown(UnknownElement::new(name)),
+        },
+        Some(Frame::Ignored) => Frame::Ignored,
+        Some(Frame::Unknown(_)) => Frame::Unknown(UnknownElement::new(name)),
+    };
+    match &mut frame {
+        Frame::Known(kind, _, node) => push_attributes(start, kind.fields(), &mut node.extra)?,
+        Frame::Unknown(element) => push_attributes(start, &[], &mut element.value)?,
+        Frame::Ignored => {}
+    }
+    Ok(frame)
+}
+
+/// Pop the current frame and attach it to the parent.
+/// Returns the root node when the root element is closed.
+fn end_frame(stack: &mut Vec<Frame>) -> Option<ExtraNode> {
+    let frame = stack.pop()?;
+    match (frame, stack.last_mut()) {
+        (Frame::Known(_, _, node), None) => return Some(node),
+        (Frame::Known(_, name, node), Some(Frame::Known(_, _, parent))) => {
+            parent.children.entry(name).or_default().push(node);
+        }
+        (Frame::Unknown(element), Some(Frame::Known(_, _, parent))) => {
+            push_element(
+                &mut parent.extra,
+                element.name.clone(),
+                element.into_value(),
+            );
+        }
+        (Frame::Unknown(element), Some(Frame::Unknown(parent))) => {
+            push_element(
+                &mut parent.value,
+                element.name.clone(),
+                element.into_value(),
+            );
+        }
+        _ => {}
+    }
+    None
+}
+
+fn collect_events(reader: &mut Reader<&[u8]>) -> Result<ExtraNode, quick_xml::Error> {
+    let mut stack: Vec<Frame> = vec![];
+    loop {
+        match reader.read_event()? {
+            Event::Start(start) => {
+                let frame = start_frame(&stack, &start)?;
+                stack.push(frame);
+            }
+            Event::Empty(start) => {
+                let frame = start_frame(&stack, &start)?;
+                stack.push(frame);
+                if let Some(root) = end_frame(&mut stack) {
+                    return Ok(root);
+                }
+            }
+            Event::End(_) => {
+                if let Some(root) = end_frame(&mut stack) {
+                    return Ok(root);
+                }
+            }
+            Event::Text(text) => {
+                if let Some(Frame::Unknown(element)) = stack.last_mut() {
+                    element.text.push_str(&text.unescape()?);
+                }
+            }
+            Event::CData(cdata) => {
+                if let Some(Frame::Unknown(element)) = stack.last_mut() {
+                    element.text.push_str(&cdata.decode()?);
+                }
+            }
+            Event::Eof => return Ok(ExtraNode::default()),
+            _ => {}
+        }
+    }
+}
+
+/// Walk the XML and collect the unrecognized items of the root element and its descendants.
+pub(crate) fn collect(xml: &str) -> Result<ExtraNode, Error> {
+    let mut reader = Reader::from_str(xml);
+    reader.config_mut().trim_text(true);
+    collect_events(&mut reader)
+        .map_err(|err| error::from_xml_error(xml, err, reader.error_position(), String::new()))
+}
+
+fn collect_json_node(kind: Kind, object: &Map<String, Value>) -> ExtraNode {
+    let mut node = ExtraNode {
+        extra: match object.get("extra") {
+            Some(Value::Object(extra)) => extra.clone(),
+            _ => Map::new(),
+        },
+        children: HashMap::new(),
+    };
+    for (key, value) in object {
+        if let Some(child) = kind.child(key) {
+            let items = match value {
+                Value::Array(items) => items.iter().collect(),
+                value => vec![value],
+            };
+            node.children.insert(
+                key.clone(),
+                items
+                    .into_iter()
+                    .filter_map(|item| item.as_object())
+                    .map(|item| collect_json_node(child, item))
+                    .collect(),
+            );
+        }
+    }
+    node
+}
+
+/// Collect `extra` of the JSON that junit2json outputs, like `{"testsuites": {...}}`.
+/// The JSON can not be deserialized into `extra`, because it is skipped when deserializing.
+pub(crate) fn collect_json(root: &Map<String, Value>) -> ExtraNode {
+    match (root.get("testsuites"), root.get("testsuite")) {
+        (Some(Value::Object(testsuites)), _) => collect_json_node(Kind::TestSuites, testsuites),
+        (_, Some(Value::Object(testsuite))) => collect_json_node(Kind::TestSuite, testsuite),
+        _ => ExtraNode::default(),
+    }
+}
+
+/// Attach the collected items to `extra` of each struct in document order.
+pub(crate) fn apply(root: &mut TestSuitesOrTestSuite, mut node: ExtraNode) {
+    match root {
+        TestSuitesOrTestSuite::TestSuites(testsuites) => apply_testsuites(testsuites, &mut node),
+        TestSuitesOrTestSuite::TestSuite(testsuite) => apply_testsuite(testsuite, &mut node),
+    }
+}
+
+fn apply_testsuites(testsuites: &mut TestSuites, node: &mut ExtraNode) {
+    testsuites.extra = node.take_extra();
+    if let Some(testsuite) = &mut testsuites.testsuite {
+        testsuite
+            .iter_mut()
+            .zip(node.take_children("testsuite").iter_mut())
+            .for_each(|(item, node)| apply_testsuite(item, node));
+    }
+}
+
+fn apply_testsuite(testsuite: &mut TestSuite, node: &mut ExtraNode) {
+    testsuite.extra = node.take_extra();
+    if let Some(testcase) = &mut testsuite.testcase {
+        testcase
+            .iter_mut()
+            .zip(node.take_children("testcase").iter_mut())
+            .for_each(|(item, node)| apply_testcase(item, node));
+    }
+    if let Some(testsuite) = &mut testsuite.testsuite {
+        testsuite
+            .iter_mut()
+            .zip(node.take_children("testsuite").iter_mut())
+            .for_each(|(item, node)| apply_testsuite(item, node));
+    }
+}
+
+fn apply_testcase(testcase: &mut TestCase, node: &mut ExtraNode) {
+    testcase.extra = node.take_extra();
+    for (details, name) in [
+        (&mut testcase.skipped, "skipped"),
+        (&mut testcase.error, "error"),
+        (&mut testcase.failure, "failure"),
+    ] {
+        if let Some(details) = details {
+            details
+                .iter_mut()
+                .zip(node.take_children(name).iter_mut())
+                .for_each(|(item, node)| item.extra = node.take_extra());
+        }
+    }
+}
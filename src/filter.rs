@@ -0,0 +1,123 @@
+//! Keep only testcases that match criteria, like failures to send to alerting.
+
+use crate::{Outcome, TestCase, TestSuite, TestSuitesOrTestSuite};
+use regex::Regex;
+
+/// `*` of file globs matches within a directory like shells, and `**` matches across directories.
+const FILE_MATCH_OPTIONS: glob::MatchOptions = glob::MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+/// Criteria of testcases to keep. See [`TestSuitesOrTestSuite::filter_testcases`].
+///
+/// A testcase is kept when it matches all of the given criteria.
+#[derive(Debug, Default, Clone)]
+pub struct TestCaseFilter {
+    /// Keep testcases whose [`TestCase::outcome`] is one of them. Empty keeps all.
+    pub outcomes: Vec<Outcome>,
+    /// Keep testcases whose `name` matches the regex
+    pub name: Option<Regex>,
+    /// Keep testcases whose `classname` matches the regex
+    pub classname: Option<Regex>,
+    /// Keep testcases whose `file` matches the glob pattern like `src/**/*.rs`.
+    /// `*` and `?` do not match `/`, so `src/*.rs` does not match `src/a/b.rs`.
+    pub file: Option<glob::Pattern>,
+    /// Keep testcases whose `time` is at least this seconds
+    pub min_time: Option<f32>,
+}
+impl TestCaseFilter {
+    /// Whether the filter has no criteria and keeps all testcases.
+    pub fn is_empty(&self) -> bool {
+        self.outcomes.is_empty()
+            && self.name.is_none()
+            && self.classname.is_none()
+            && self.file.is_none()
+            && self.min_time.is_none()
+    }
+
+    /// Whether the testcase matches all of the criteria.
+    pub fn matches(&self, testcase: &TestCase) -> bool {
+        let regex_matches = |regex: &Option<Regex>, value: &Option<String>| match regex {
+            Some(regex) => value.as_deref().is_some_and(|value| regex.is_match(value)),
+            None => true,
+        };
+        (self.outcomes.is_empty() || self.outcomes.contains(&testcase.outcome()))
+            && regex_matches(&self.name, &testcase.name)
+            && regex_matches(&self.classname, &testcase.classname)
+            && self.file.as_ref().is_none_or(|pattern| {
+                testcase
+                    .file
+                    .as_deref()
+                    .is_some_and(|file| pattern.matches_with(file, FILE_MATCH_OPTIONS))
+            })
+            && self
+                .min_time
+                .is_none_or(|min_time| testcase.time.is_some_and(|time| time >= min_time))
+    }
+}
+
+impl TestSuitesOrTestSuite {
+    /// Keep only testcases that match the filter, and remove `<testsuite>` elements that become empty.
+    ///
+    /// The counters like `tests` and `failures` are kept as they are.
+    /// Call [`TestSuitesOrTestSuite::recompute_counters`] to make them agree with the kept testcases.
+    ///
+    /// # Examples
+    /// ```
+    /// use junit2json;
+    ///
+    /// let xml = r#"
+    ///   <testsuites>
+    ///       <testsuite name="suite1">
+    ///           <testcase name="case1" />
+    ///           <testcase name="case2"><failure /></testcase>
+    ///       </testsuite>
+    ///       <testsuite name="suite2">
+    ///           <testcase name="case3" />
+    ///       </testsuite>
+    ///   </testsuites>
+    /// "#;
+    /// let mut testsuites = junit2json::from_str(xml).unwrap();
+    /// let filter = junit2json::TestCaseFilter {
+    ///     outcomes: vec![junit2json::Outcome::Failed, junit2json::Outcome::Errored],
+    ///     ..Default::default()
+    /// };
+    /// testsuites.filter_testcases(&filter);
+    /// testsuites.recompute_counters();
+    /// println!("{:#?}", testsuites);
+    /// ```
+    pub fn filter_testcases(&mut self, filter: &TestCaseFilter) {
+        match self {
+            TestSuitesOrTestSuite::TestSuites(testsuites) => {
+                filter_children(&mut testsuites.testsuite, filter);
+            }
+            TestSuitesOrTestSuite::TestSuite(testsuite) => {
+                filter_testsuite(testsuite, filter);
+            }
+        }
+    }
+}
+
+/// Filter testcases of the testsuite, and returns whether it became empty.
+fn filter_testsuite(testsuite: &mut TestSuite, filter: &TestCaseFilter) -> bool {
+    let had_children = testsuite.testcase.is_some() || testsuite.testsuite.is_some();
+    if let Some(testcase) = &mut testsuite.testcase {
+        testcase.retain(|item| filter.matches(item));
+        if testcase.is_empty() {
+            testsuite.testcase = None;
+        }
+    }
+    filter_children(&mut testsuite.testsuite, filter);
+    had_children && testsuite.testcase.is_none() && testsuite.testsuite.is_none()
+}
+
+fn filter_children(testsuite: &mut Option<Vec<TestSuite>>, filter: &TestCaseFilter) {
+    if let Some(items) = testsuite {
+        items.retain_mut(|item| !filter_testsuite(item, filter));
+        if items.is_empty() {
+            *testsuite = None;
+        }
+    }
+}
@@ -0,0 +1,311 @@
+//! Remove elements and attributes by selectors like `properties`, `testcase.file` or `failure.inner`.
+
+use crate::cli::PossibleFilterTags;
+use crate::{Detail, Rerun, TestCase, TestSuite, TestSuites, TestSuitesOrTestSuite};
+use std::fmt;
+use std::str::FromStr;
+
+/// Elements that a selector can be scoped to.
+const SCOPES: [&str; 10] = [
+    "testsuites",
+    "testsuite",
+    "testcase",
+    "skipped",
+    "error",
+    "failure",
+    "flakyFailure",
+    "flakyError",
+    "rerunFailure",
+    "rerunError",
+];
+
+/// Child elements of `testcase`. Without scope, they select only the element,
+/// not the counter attribute of the same name like `skipped` of `testsuite`.
+const TESTCASE_ELEMENTS: [&str; 7] = [
+    "skipped",
+    "error",
+    "failure",
+    "flakyFailure",
+    "flakyError",
+    "rerunFailure",
+    "rerunError",
+];
+
+/// Selector of an element or attribute to remove, written as `[scope.]field` with the names of the JSON.
+///
+/// - `system-out` removes `system-out` of every `testsuite`, `testcase` and rerun like `flakyFailure`.
+/// - `skipped` removes the `skipped` element of `testcase`, and `testsuite.skipped` removes the counter of `testsuite`.
+/// - `testsuite.properties` removes `properties` only of `testsuite`.
+/// - `failure.inner` removes the text of `failure`, like a full stack trace, but keeps its `message`.
+///
+/// # Examples
+/// ```
+/// use junit2json;
+///
+/// let tag: junit2json::FilterTag = "testcase.file".parse().unwrap();
+/// assert_eq!(tag.scope, Some("testcase".to_string()));
+/// assert_eq!(tag.field, "file");
+/// assert!("testcase.unknown".parse::<junit2json::FilterTag>().is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct FilterTag {
+    /// Element like `testsuite` or `failure`. `None` matches every element that has the field.
+    pub scope: Option<String>,
+    /// Element or attribute like `properties`, `hostname` or `inner`
+    pub field: String,
+}
+impl FilterTag {
+    fn applies_to(&self, scope: &str) -> bool {
+        match &self.scope {
+            Some(item) => item == scope,
+            None => scope == "testcase" || !TESTCASE_ELEMENTS.contains(&self.field.as_str()),
+        }
+    }
+}
+impl FromStr for FilterTag {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (scope, field) = match s.split_once('.') {
+            Some((scope, field)) => (Some(scope), field),
+            None => (None, s),
+        };
+        if let Some(scope) = scope.filter(|scope| !SCOPES.contains(scope)) {
+            return Err(format!(
+                "unknown element {}, expected one of {}",
+                scope,
+                SCOPES.join(", ")
+            ));
+        }
+        let tag = FilterTag {
+            scope: scope.map(|scope| scope.to_string()),
+            field: field.to_string(),
+        };
+        let known = SCOPES
+            .iter()
+            .filter(|scope| tag.applies_to(scope))
+            .any(|scope| has_field(scope, field));
+        match known {
+            true => Ok(tag),
+            false => Err(format!("unknown field {}", s)),
+        }
+    }
+}
+impl fmt::Display for FilterTag {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.scope {
+            Some(scope) => write!(f, "{}.{}", scope, self.field),
+            None => write!(f, "{}", self.field),
+        }
+    }
+}
+impl From<&PossibleFilterTags> for FilterTag {
+    fn from(tag: &PossibleFilterTags) -> Self {
+        let field = match tag {
+            PossibleFilterTags::SystemOut => "system-out",
+            PossibleFilterTags::SystemErr => "system-err",
+        };
+        FilterTag {
+            scope: None,
+            field: field.to_string(),
+        }
+    }
+}
+
+/// Whether the element has the field, checked by removing it from an empty struct,
+/// so the known fields never drift from `remove_field` of each struct.
+fn has_field(scope: &str, field: &str) -> bool {
+    match scope {
+        "testsuites" => TestSuites::default().remove_field(field),
+        "testsuite" => TestSuite::default().remove_field(field),
+        "testcase" => TestCase::default().remove_field(field),
+        "skipped" | "error" | "failure" => Detail::default().remove_field(field),
+        _ => Rerun::default().remove_field(field),
+    }
+}
+
+/// Remove the fields that the tags select in the scope. `remove` returns whether the field is known.
+fn remove_fields(scope: &str, tags: &[FilterTag], mut remove: impl FnMut(&str) -> bool) {
+    for tag in tags.iter().filter(|tag| tag.applies_to(scope)) {
+        remove(&tag.field);
+    }
+}
+
+impl TestSuitesOrTestSuite {
+    /// Remove elements and attributes that the tags select from every `testsuites`, `testsuite`, `testcase` and their children.
+    ///
+    /// # Examples
+    /// ```
+    /// use junit2json;
+    ///
+    /// let xml = r#"
+    ///   <testsuite name="suite1" hostname="runner-01">
+    ///       <properties><property name="PATH" value="/usr/bin" /></properties>
+    ///       <testcase name="case1" file="src/lib.rs">
+    ///           <failure message="failed">long stack trace</failure>
+    ///       </testcase>
+    ///   </testsuite>
+    /// "#;
+    /// let mut testsuite = junit2json::from_str(xml).unwrap();
+    /// let tags = ["properties", "hostname", "failure.inner"].map(|tag| tag.parse().unwrap());
+    /// testsuite.remove_fields(&tags);
+    /// println!("{:#?}", testsuite);
+    /// ```
+    pub fn remove_fields(&mut self, tags: &[FilterTag]) {
+        match self {
+            TestSuitesOrTestSuite::TestSuites(testsuites) => testsuites.remove_fields(tags),
+            TestSuitesOrTestSuite::TestSuite(testsuite) => testsuite.remove_fields(tags),
+        }
+    }
+}
+
+impl TestSuites {
+    /// Remove elements and attributes that the tags select. See [`TestSuitesOrTestSuite::remove_fields`].
+    pub fn remove_fields(&mut self, tags: &[FilterTag]) {
+        remove_fields("testsuites", tags, |field| self.remove_field(field));
+        if let Some(testsuite) = &mut self.testsuite {
+            testsuite
+                .iter_mut()
+                .for_each(|item| item.remove_fields(tags));
+        }
+    }
+    fn remove_field(&mut self, field: &str) -> bool {
+        match field {
+            "name" => self.name = None,
+            "time" => self.time = None,
+            "tests" => self.tests = None,
+            "failures" => self.failures = None,
+            "errors" => self.errors = None,
+            "skipped" => self.skipped = None,
+            "assertions" => self.assertions = None,
+            "timestamp" => self.timestamp = None,
+            "extra" => self.extra = None,
+            _ => return false,
+        }
+        true
+    }
+}
+
+impl TestSuite {
+    /// Remove elements and attributes that the tags select. See [`TestSuitesOrTestSuite::remove_fields`].
+    pub fn remove_fields(&mut self, tags: &[FilterTag]) {
+        remove_fields("testsuite", tags, |field| self.remove_field(field));
+        if let Some(testcase) = &mut self.testcase {
+            testcase
+                .iter_mut()
+                .for_each(|item| item.remove_fields(tags));
+        }
+        if let Some(testsuite) = &mut self.testsuite {
+            testsuite
+                .iter_mut()
+                .for_each(|item| item.remove_fields(tags));
+        }
+    }
+    fn remove_field(&mut self, field: &str) -> bool {
+        match field {
+            "name" => self.name = None,
+            "tests" => self.tests = None,
+            "failures" => self.failures = None,
+            "errors" => self.errors = None,
+            "group" => self.group = None,
+            "time" => self.time = None,
+            "disabled" => self.disabled = None,
+            "skipped" => self.skipped = None,
+            "timestamp" => self.timestamp = None,
+            "hostname" => self.hostname = None,
+            "id" => self.id = None,
+            "package" => self.package = None,
+            "file" => self.file = None,
+            "log" => self.log = None,
+            "url" => self.url = None,
+            "system-out" => self.system_out = None,
+            "system-err" => self.system_err = None,
+            "properties" => self.properties = None,
+            "extra" => self.extra = None,
+            _ => return false,
+        }
+        true
+    }
+}
+
+impl TestCase {
+    /// Remove elements and attributes that the tags select from the testcase and its children.
+    /// See [`TestSuitesOrTestSuite::remove_fields`].
+    pub fn remove_fields(&mut self, tags: &[FilterTag]) {
+        remove_fields("testcase", tags, |field| self.remove_field(field));
+        for (scope, details) in [
+            ("skipped", &mut self.skipped),
+            ("error", &mut self.error),
+            ("failure", &mut self.failure),
+        ] {
+            for detail in details.iter_mut().flatten() {
+                remove_fields(scope, tags, |field| detail.remove_field(field));
+            }
+        }
+        for (scope, reruns) in [
+            ("flakyFailure", &mut self.flaky_failure),
+            ("flakyError", &mut self.flaky_error),
+            ("rerunFailure", &mut self.rerun_failure),
+            ("rerunError", &mut self.rerun_error),
+        ] {
+            for rerun in reruns.iter_mut().flatten() {
+                remove_fields(scope, tags, |field| rerun.remove_field(field));
+            }
+        }
+    }
+    fn remove_field(&mut self, field: &str) -> bool {
+        match field {
+            "name" => self.name = None,
+            "classname" => self.classname = None,
+            "assertions" => self.assertions = None,
+            "time" => self.time = None,
+            "status" => self.status = None,
+            "file" => self.file = None,
+            "line" => self.line = None,
+            "timestamp" => self.timestamp = None,
+            "hostname" => self.hostname = None,
+            "system-out" => self.system_out = None,
+            "system-err" => self.system_err = None,
+            "properties" => self.properties = None,
+            "skipped" => self.skipped = None,
+            "error" => self.error = None,
+            "failure" => self.failure = None,
+            "flakyFailure" => self.flaky_failure = None,
+            "flakyError" => self.flaky_error = None,
+            "rerunFailure" => self.rerun_failure = None,
+            "rerunError" => self.rerun_error = None,
+            "outcome" => self.outcome = None,
+            "extra" => self.extra = None,
+            _ => return false,
+        }
+        true
+    }
+}
+
+impl Detail {
+    fn remove_field(&mut self, field: &str) -> bool {
+        match field {
+            "message" => self.message = None,
+            "type" => self.r#type = None,
+            "inner" => self.inner = None,
+            "extra" => self.extra = None,
+            _ => return false,
+        }
+        true
+    }
+}
+
+impl Rerun {
+    fn remove_field(&mut self, field: &str) -> bool {
+        match field {
+            "message" => self.message = None,
+            "type" => self.r#type = None,
+            "timestamp" => self.timestamp = None,
+            "time" => self.time = None,
+            "stackTrace" => self.stack_trace = None,
+            "system-out" => self.system_out = None,
+            "system-err" => self.system_err = None,
+            _ => return false,
+        }
+        true
+    }
+}
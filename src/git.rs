@@ -0,0 +1,185 @@
+//! Read the commit, branch, author and commit time of a local git repository with gix,
+//! without running `git` or accessing the network.
+
+use crate::{SuiteContext, TestCase, TestSuite, TestSuitesOrTestSuite};
+use serde_json::{Map, Value};
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn invalid_data(err: impl std::error::Error + Send + Sync + 'static) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err)
+}
+
+/// Git information of the repository that contains a directory. See [`GitInfo::discover`].
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct GitInfo {
+    /// Root of the work tree
+    pub root: PathBuf,
+    /// The directory that the repository is discovered from, that relative `file` paths are resolved from
+    pub dir: PathBuf,
+    /// SHA of `HEAD`. `None` before the first commit
+    pub commit: Option<String>,
+    /// Branch of `HEAD`. `None` when `HEAD` is detached like in most CIs
+    pub branch: Option<String>,
+    /// Author of the commit like `Name <email>`
+    pub author: Option<String>,
+    /// Committer time of the commit in RFC 3339 with its offset like `2024-01-02T03:04:05+09:00`
+    pub commit_time: Option<String>,
+}
+
+impl GitInfo {
+    /// Read the git information of the repository that contains `dir` or its ancestors.
+    ///
+    /// # Examples
+    /// ```
+    /// use junit2json;
+    /// use std::path::Path;
+    ///
+    /// if let Ok(git) = junit2json::GitInfo::discover(Path::new(".")) {
+    ///     println!("{:?} {:?}", git.commit, git.branch);
+    /// }
+    /// ```
+    pub fn discover(dir: &Path) -> io::Result<GitInfo> {
+        let dir = dir.canonicalize()?;
+        let repository = gix::discover(&dir).map_err(|err| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("not a git repository: {}: {}", dir.display(), err),
+            )
+        })?;
+        let root = repository
+            .workdir()
+            .ok_or_else(|| invalid(format!("bare repository: {}", dir.display())))?
+            .canonicalize()?;
+        let head = repository.head().map_err(invalid_data)?;
+        let mut info = GitInfo {
+            root,
+            dir,
+            commit: head.id().map(|id| id.to_string()),
+            branch: head.referent_name().map(|name| name.shorten().to_string()),
+            ..Default::default()
+        };
+        if let Some(id) = head.id() {
+            let commit = repository.find_commit(id).map_err(invalid_data)?;
+            let author = commit.author().map_err(invalid_data)?;
+            info.author = Some(format!("{} <{}>", author.name, author.email));
+            let time = commit.committer().map_err(invalid_data)?.time();
+            info.commit_time = Some(
+                time.map_err(invalid_data)?
+                    .format(gix::date::time::format::ISO8601_STRICT),
+            );
+        }
+        Ok(info)
+    }
+
+    /// Returns `commit`, `branch`, `author` and `commit_time` for [`crate::add_metadata`]. Absent ones are omitted.
+    pub fn to_metadata(&self) -> Map<String, Value> {
+        [
+            ("commit", &self.commit),
+            ("branch", &self.branch),
+            ("author", &self.author),
+            ("commit_time", &self.commit_time),
+        ]
+        .into_iter()
+        .filter_map(|(key, value)| Some((key.to_string(), Value::String(value.clone()?))))
+        .collect()
+    }
+
+    /// Returns the path relative to the root of the repository with `/` separators,
+    /// or `None` when the path is outside of the repository.
+    /// A relative path is resolved from [`GitInfo::dir`].
+    ///
+    /// # Examples
+    /// ```
+    /// use junit2json;
+    /// use std::path::PathBuf;
+    ///
+    /// let git = junit2json::GitInfo {
+    ///     root: PathBuf::from("/home/user/repo"),
+    ///     dir: PathBuf::from("/home/user/repo/packages/app"),
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(git.relative_path("src/index.test.js"), Some("packages/app/src/index.test.js".to_string()));
+    /// assert_eq!(git.relative_path("/home/user/repo/src/lib.rs"), Some("src/lib.rs".to_string()));
+    /// assert_eq!(git.relative_path("../../../other/lib.rs"), None);
+    /// ```
+    pub fn relative_path(&self, file: &str) -> Option<String> {
+        let path = self.dir.join(file);
+        let path = path.canonicalize().unwrap_or_else(|_| normalize(&path));
+        let relative = path.strip_prefix(&self.root).ok()?;
+        let components = relative
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>();
+        Some(components.join("/"))
+    }
+}
+
+/// Resolve `.` and `..` of a path that does not exist, without following symlinks.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+impl TestSuitesOrTestSuite {
+    /// Resolve `file` of every `testsuite` and `testcase` to the path relative to the root of the repository.
+    /// Paths outside of the repository are kept. See [`GitInfo::relative_path`].
+    pub fn relativize_files(&mut self, git: &GitInfo) {
+        match self {
+            TestSuitesOrTestSuite::TestSuites(testsuites) => testsuites
+                .testsuite
+                .iter_mut()
+                .flatten()
+                .for_each(|item| item.relativize_files(git)),
+            TestSuitesOrTestSuite::TestSuite(testsuite) => testsuite.relativize_files(git),
+        }
+    }
+}
+
+impl TestSuite {
+    /// Resolve `file` to the path relative to the root of the repository. See [`TestSuitesOrTestSuite::relativize_files`].
+    pub fn relativize_files(&mut self, git: &GitInfo) {
+        relativize(&mut self.file, git);
+        for testcase in self.testcase.iter_mut().flatten() {
+            testcase.relativize_files(git);
+        }
+        for testsuite in self.testsuite.iter_mut().flatten() {
+            testsuite.relativize_files(git);
+        }
+    }
+}
+
+impl TestCase {
+    /// Resolve `file` to the path relative to the root of the repository. See [`TestSuitesOrTestSuite::relativize_files`].
+    pub fn relativize_files(&mut self, git: &GitInfo) {
+        relativize(&mut self.file, git);
+    }
+}
+
+impl SuiteContext {
+    /// Resolve `file` of the enclosing `testsuite` elements. See [`TestSuitesOrTestSuite::relativize_files`].
+    pub fn relativize_files(&mut self, git: &GitInfo) {
+        for testsuite in self.testsuite.iter_mut() {
+            relativize(&mut testsuite.file, git);
+        }
+    }
+}
+
+fn relativize(file: &mut Option<String>, git: &GitInfo) {
+    if let Some(relative) = file.as_deref().and_then(|file| git.relative_path(file)) {
+        *file = Some(relative);
+    }
+}
@@ -0,0 +1,137 @@
+//! Deserialize the JSON that junit2json outputs back to the structs.
+//!
+//! The structs are deserialized from XML names like `@name` and `$value`,
+//! so the keys of the JSON are renamed to them before deserializing.
+//! The attribute names are taken from the structs, so they never drift from the structs.
+
+use crate::extra::{self, field_names};
+use crate::{
+    Detail, Properties, Property, Rerun, TestCase, TestSuite, TestSuites, TestSuitesOrTestSuite,
+};
+use serde_json::{Map, Value};
+use std::io;
+
+#[derive(Clone, Copy)]
+enum Kind {
+    TestSuites,
+    TestSuite,
+    TestCase,
+    Detail,
+    Rerun,
+    Properties,
+    Property,
+}
+impl Kind {
+    fn fields(&self) -> &'static [&'static str] {
+        match self {
+            Kind::TestSuites => field_names::<TestSuites>(),
+            Kind::TestSuite => field_names::<TestSuite>(),
+            Kind::TestCase => field_names::<TestCase>(),
+            Kind::Detail => field_names::<Detail>(),
+            Kind::Rerun => field_names::<Rerun>(),
+            Kind::Properties => field_names::<Properties>(),
+            Kind::Property => field_names::<Property>(),
+        }
+    }
+    fn child(&self, name: &str) -> Option<Kind> {
+        match (self, name) {
+            (Kind::TestSuites | Kind::TestSuite, "testsuite") => Some(Kind::TestSuite),
+            (Kind::TestSuite, "testcase") => Some(Kind::TestCase),
+            (Kind::TestSuite | Kind::TestCase, "properties") => Some(Kind::Properties),
+            (Kind::TestCase, "skipped" | "error" | "failure") => Some(Kind::Detail),
+            (Kind::TestCase, "flakyFailure" | "flakyError" | "rerunFailure" | "rerunError") => {
+                Some(Kind::Rerun)
+            }
+            (Kind::Properties, "property") => Some(Kind::Property),
+            _ => None,
+        }
+    }
+}
+
+/// Keys that the CLI adds next to the root, like `path` of `--output ndjson` and `metadata` of `--meta`.
+/// They are not a part of the report.
+const ADDED_KEYS: &[&str] = &["path", "metadata"];
+
+/// Rename the keys of the JSON object to the names that the struct is deserialized from.
+fn rename_keys(kind: Kind, object: &mut Map<String, Value>) {
+    let fields = kind.fields();
+    let renamed = std::mem::take(object)
+        .into_iter()
+        .map(|(key, mut value)| {
+            if let Some(child) = kind.child(&key) {
+                // `skipped`, `error` and `failure` are an object in `DetailShape::Object`
+                if let (Kind::Detail, Value::Object(_)) = (child, &value) {
+                    value = Value::Array(vec![value]);
+                }
+                match &mut value {
+                    Value::Array(items) => items
+                        .iter_mut()
+                        .filter_map(|item| item.as_object_mut())
+                        .for_each(|item| rename_keys(child, item)),
+                    Value::Object(item) => rename_keys(child, item),
+                    _ => {}
+                }
+            }
+            let attribute = format!("@{}", key);
+            let key = match key.as_str() {
+                _ if fields.contains(&attribute.as_str()) => attribute,
+                "inner" if fields.contains(&"$value") => "$value".to_string(),
+                _ => key,
+            };
+            (key, value)
+        })
+        .collect();
+    *object = renamed;
+}
+
+/// Deserialize the JSON that junit2json outputs, like `{"testsuites": {...}}` or `{"testsuite": {...}}`.
+///
+/// Both shapes of `skipped`, `error` and `failure` in [`crate::cli::DetailShape`] are accepted,
+/// and `extra` of the lossless mode is restored.
+/// `path` that the CLI adds for many files and `metadata` that [`crate::add_metadata`] adds are ignored.
+///
+/// # Examples
+/// ```
+/// use junit2json;
+///
+/// let json = r#"{"testsuite": {"name": "suite1", "testcase": [{"name": "case1", "failure": {"inner": "failed"}}]}}"#;
+/// let testsuite = junit2json::from_json_str(json).unwrap();
+/// println!("{}", junit2json::to_string(&testsuite).unwrap());
+/// ```
+pub fn from_json_str(s: &str) -> Result<TestSuitesOrTestSuite, serde_json::Error> {
+    from_json_value(serde_json::from_str(s)?)
+}
+
+/// Deserialize the JSON that junit2json outputs from a reader. See [`from_json_str`].
+pub fn from_json_reader<R: io::Read>(
+    reader: R,
+) -> Result<TestSuitesOrTestSuite, serde_json::Error> {
+    from_json_value(serde_json::from_reader(reader)?)
+}
+
+/// Deserialize `serde_json::Value` that junit2json outputs. See [`from_json_str`].
+pub fn from_json_value(mut value: Value) -> Result<TestSuitesOrTestSuite, serde_json::Error> {
+    let node = match value.as_object_mut() {
+        Some(object) => {
+            for key in ADDED_KEYS {
+                object.remove(*key);
+            }
+            let node = extra::collect_json(object);
+            for (key, kind) in [
+                ("testsuites", Kind::TestSuites),
+                ("testsuite", Kind::TestSuite),
+            ] {
+                if let Some(Value::Object(root)) = object.get_mut(key) {
+                    rename_keys(kind, root);
+                }
+            }
+            Some(node)
+        }
+        None => None,
+    };
+    let mut root: TestSuitesOrTestSuite = serde_json::from_value(value)?;
+    if let Some(node) = node {
+        extra::apply(&mut root, node);
+    }
+    Ok(root)
+}
@@ -0,0 +1,125 @@
+//! Repair malformed or truncated JUnit XML for the lenient mode.
+//!
+//! The XML is rewritten event by event before it is passed to serde:
+//! - Attributes that can not be deserialized, like `time="N/A"`, are dropped, so they become `None`.
+//! - When the XML ends unexpectedly or has a syntax error, the incomplete `<testcase>` is dropped
+//!   and the other open elements are closed, so every complete `<testcase>` and `<testsuite>` is kept.
+
+use crate::error::{self, ElementPath, Error, Location, Warning};
+use quick_xml::events::{BytesEnd, BytesStart, Event};
+use quick_xml::{Reader, Writer};
+
+/// Element that is not closed yet
+struct OpenElement {
+    name: String,
+    path: String,
+    offset: u64,
+    /// Length of the output before the start tag of this element
+    output_len: usize,
+}
+
+/// Rebuild the start tag without the attributes that can not be deserialized.
+fn sanitize<'a>(
+    xml: &str,
+    path: &ElementPath,
+    start: BytesStart<'a>,
+    offset: u64,
+    warnings: &mut Vec<Warning>,
+) -> Result<BytesStart<'a>, quick_xml::Error> {
+    let invalid = error::check_attributes(path, &start)?;
+    if invalid.is_empty() {
+        return Ok(start);
+    }
+    let mut sanitized = start.to_owned();
+    sanitized.clear_attributes();
+    for attr in start.attributes() {
+        let attr = attr?;
+        match invalid
+            .iter()
+            .find(|item| item.name.as_bytes() == attr.key.as_ref())
+        {
+            Some(item) => warnings.push(Warning {
+                message: format!("ignored invalid value {:?} ({})", item.value, item.message),
+                location: Location::new(xml, offset, path.with_attribute(&item.name)),
+            }),
+            None => {
+                sanitized.push_attribute(attr);
+            }
+        }
+    }
+    Ok(sanitized)
+}
+
+/// Returns the repaired XML and the warnings of what is repaired.
+pub(crate) fn repair(xml: &str) -> Result<(String, Vec<Warning>), Error> {
+    let mut reader = Reader::from_str(xml);
+    let mut writer = Writer::new(Vec::new());
+    let mut path = ElementPath::default();
+    let mut open: Vec<OpenElement> = vec![];
+    let mut warnings = vec![];
+
+    loop {
+        let offset = reader.buffer_position();
+        let event = reader.read_event().and_then(|event| match event {
+            Event::Start(start) => {
+                path.push(&String::from_utf8_lossy(start.name().as_ref()));
+                let start = sanitize(xml, &path, start, offset, &mut warnings)?;
+                open.push(OpenElement {
+                    name: String::from_utf8_lossy(start.name().as_ref()).into_owned(),
+                    path: path.to_string(),
+                    offset,
+                    output_len: writer.get_ref().len(),
+                });
+                Ok(Event::Start(start))
+            }
+            Event::Empty(start) => {
+                path.push(&String::from_utf8_lossy(start.name().as_ref()));
+                let start = sanitize(xml, &path, start, offset, &mut warnings)?;
+                path.pop();
+                Ok(Event::Empty(start))
+            }
+            Event::End(end) => {
+                path.pop();
+                open.pop();
+                Ok(Event::End(end))
+            }
+            event => Ok(event),
+        });
+        match event {
+            Ok(Event::Eof) => {
+                if !open.is_empty() {
+                    warnings.push(Warning {
+                        message: "unexpected end of XML".to_string(),
+                        location: Location::new(xml, offset, path.to_string()),
+                    });
+                }
+                break;
+            }
+            Ok(event) => writer.write_event(event)?,
+            Err(err) => {
+                warnings.push(Warning {
+                    message: format!("ignored the rest of XML after syntax error: {}", err),
+                    location: Location::new(xml, reader.error_position(), path.to_string()),
+                });
+                break;
+            }
+        }
+    }
+
+    if let Some(index) = open.iter().rposition(|element| element.name == "testcase") {
+        let testcase = &open[index];
+        warnings.push(Warning {
+            message: "dropped incomplete testcase".to_string(),
+            location: Location::new(xml, testcase.offset, testcase.path.clone()),
+        });
+        writer.get_mut().truncate(testcase.output_len);
+        open.truncate(index);
+    }
+    for element in open.iter().rev() {
+        writer.write_event(Event::End(BytesEnd::new(element.name.as_str())))?;
+    }
+
+    let repaired = String::from_utf8(writer.into_inner())
+        .map_err(|err| Error::Io(std::io::Error::new(std::io::ErrorKind::InvalidData, err)))?;
+    Ok((repaired, warnings))
+}
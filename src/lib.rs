@@ -14,15 +14,21 @@
 //! For this purpose, junit2json-rs provides a simple JUnit XML to JSON converter.
 //!
 //! # Install
-//! ```
+//! ```shell
 //! cargo install junit2json
 //! ```
 //!
 //! # Usage
-//! ```
+//! ```shell
 //! junit2json -p <junit_xml_file>
 //! ```
 //!
+//! It reads from stdin when the path is omitted or `-`.
+//!
+//! ```shell
+//! pytest --junitxml=/dev/stdout | junit2json -p
+//! ```
+//!
 //! # Output example
 //! ```json
 //! {
@@ -108,65 +114,231 @@
 //! # With `jq` examples
 //! Show testsuites test count
 //!
-//! ```
+//! ```shell
 //! junit2json <junit_xml_file> | jq .testsuites.tests
 //! ```
 //!
 //! Show testsuite names
 //!
-//! ```
+//! ```shell
 //! junit2json <junit_xml_file> | jq .testsuites.testsuite[].name
 //! ```
 //!
 //! Show testcase classnames
 //!
-//! ```
+//! ```shell
 //! junit2json <junit_xml_file> | jq .testsuites.testsuite[].testcase[].classname
 //! ```
 //!
+//! Show failed testcase names with `--outcome`, that adds `outcome` of each testcase
+//!
+//! ```shell
+//! junit2json --outcome <junit_xml_file> | jq '.testsuites.testsuite[].testcase[] | select(.outcome == "failed") | .name'
+//! ```
+//!
+//! # Multiple files
+//! Paths can be many files, glob patterns or directories that are searched for `*.xml` recursively.
+//! `--output` selects one JSON document per file, a JSON `array` or `ndjson` (one JSON per line),
+//! and each JSON has `path` of its file as the first key.
+//! `--stream` and `--flat` lines have `path` too.
+//!
+//! ```shell
+//! junit2json --output ndjson 'build/test-results/**/TEST-*.xml' > results.ndjson
+//! junit2json --output array -p build/test-results | jq '.[] | {path, tests: .testsuite.tests}'
+//! ```
+//!
+//! # Merge reports
+//! `merge` subcommand merges many reports, like the ones of sharded CI jobs, into one `testsuites`.
+//! `tests`, `failures`, `errors`, `skipped` and `time` of it are the sums of the reports.
+//! A testsuite that lacks `tests` or whose counters disagree with its testcases is recounted from the testcases before summing.
+//! `--merge-same-name` merges testsuites with the same name into one, and `--xml` outputs JUnit XML instead of JSON.
+//!
+//! ```shell
+//! junit2json merge --merge-same-name --xml 'shard-*/junit.xml' > junit.xml
+//! ```
+//!
+//! # Fix counters
+//! Some generators write wrong or missing `tests`, `failures`, `errors`, `skipped` and `disabled` on testsuites.
+//! `--counters fix` recomputes them from the testcases and sets `time` to the sum of the testcases,
+//! and `--counters warn` prints every counter that disagrees with the testcases to stderr.
+//! Because `time` is wall time that is shorter than the sum with parallel testcases,
+//! it is only reported when it is shorter than the longest testcase or nested testsuite.
+//!
+//! ```shell
+//! junit2json --counters fix <junit_xml_file>
+//! ```
+//!
+//! # Remove fields
+//! `--filter-tags` removes elements or attributes to make the JSON small, like `system-out`, `properties` with many environment variables
+//! or `failure.inner` with a full stack trace. A selector is `[scope.]field` with the names of the JSON,
+//! and a field without scope is removed from every element that has it.
+//! Elements of testcases like `skipped` are removed only from testcases, so use `testsuite.skipped` to remove the counter.
+//!
+//! ```shell
+//! junit2json --filter-tags system-out,system-err,testsuite.properties,failure.inner <junit_xml_file>
+//! ```
+//!
+//! # Filter testcases
+//! `--only-outcome`, `--only-name`, `--only-classname`, `--only-file` and `--min-time` keep only testcases that match all of them,
+//! and testsuites that become empty are removed. With `--counters fix`, the counters agree with the kept testcases.
+//!
+//! ```shell
+//! junit2json --only-outcome failed,errored --counters fix <junit_xml_file>
+//! junit2json --only-classname '^com\.example\.' --min-time 10 --flat <junit_xml_file>
+//! ```
+//!
+//! # Strip escape sequences
+//! `--strip ansi` strips ANSI escape sequences like colors of test runners from `system-out`, `system-err` and texts of `failure` and others.
+//! `--strip orphan-sgr` strips the ones whose ESC is lost like `[31m` too. It is opt-in because it can strip ordinary texts like `[0m`.
+//! `--strip control` strips other control characters too, except tab and line breaks.
+//!
+//! ```shell
+//! junit2json --strip ansi <junit_xml_file>
+//! ```
+//!
+//! # Redact secrets
+//! `--redact` replaces AWS keys, GitHub tokens, JWTs, bearer tokens and private keys in every string with `[REDACTED]`,
+//! like tokens in env dumps of `<property>` and logs of `system-out`.
+//! `--redact-pattern` adds a regex, and only its `secret` group is replaced when it has one.
+//! `--redact-property` replaces the whole value of properties whose name matches the glob case-insensitively.
+//! The number of redactions is printed to stderr.
+//!
+//! ```shell
+//! junit2json --redact --redact-property '*TOKEN*,*SECRET*,*PASSWORD*' --redact-pattern 'password=(?P<secret>\S+)' <junit_xml_file>
+//! ```
+//!
+//! # Add metadata
+//! `--meta key=value` adds `metadata` object next to `testsuites` or `testsuite`, and to each line of `--stream` and `--flat`.
+//! `--meta-ci` detects the commit, branch, pipeline and job from environment variables of GitHub Actions, GitLab CI, CircleCI, Jenkins and Buildkite.
+//! `schema --metadata` adds `metadata` to the schema.
+//!
+//! ```shell
+//! junit2json --meta-ci --meta env=staging --flat <junit_xml_file> > testcases.ndjson
+//! junit2json schema --flat --metadata > schema.json
+//! ```
+//!
+//! # Add git information
+//! `--git` adds `commit`, `branch`, `author` and `commit_time` of the git repository of the current directory to `metadata`,
+//! or of the directory like `--git=path/to/repo`. It reads `.git` with the gix crate, so it needs neither `git` nor the network.
+//! `--git-relative-file` resolves `file` of testsuites and testcases to the path relative to the root of the repository.
+//!
+//! ```shell
+//! junit2json --git --git-relative-file <junit_xml_file>
+//! ```
+//!
+//! # Load into BigQuery
+//! `--flat` outputs one JSON object per testcase per line with the fields of its testsuite,
+//! that can be loaded into BigQuery as rows.
+//! `schema` subcommand outputs the BigQuery table schema for it, or for the JSON document without `--flat`.
+//! `schema --format json-schema` outputs JSON Schema (draft 2020-12) instead, that is a contract of the JSON for other consumers.
+//! Both schemas have the optional `path` of the input file, so the output of many files is valid against them too.
+//!
+//! ```shell
+//! junit2json --flat <junit_xml_file> > testcases.ndjson
+//! junit2json schema --flat > schema.json
+//! bq load --source_format=NEWLINE_DELIMITED_JSON <dataset>.<table> testcases.ndjson schema.json
+//! ```
+//!
+//! BigQuery rejects rows larger than its limit, so truncate large texts like `system-out`.
+//! `--max-output-bytes` and `--max-detail-bytes` cut each text at a UTF-8 character boundary
+//! with a marker like `[... truncated from 10485760 bytes ...]`, and `--truncate-keep` selects to keep its head, tail or both.
+//!
+//! ```shell
+//! junit2json --flat --max-output-bytes 1000000 --max-detail-bytes 100000 --truncate-keep both <junit_xml_file> > testcases.ndjson
+//! ```
+//!
+//! # Convert JSON back to JUnit XML
+//! `to-xml` subcommand converts the JSON of junit2json back to JUnit XML for tools that only accept JUnit XML.
+//! It reads the JSON from stdin when the path is omitted, and ignores `path` of `--output ndjson` and `metadata` of `--meta`.
+//!
+//! ```shell
+//! junit2json <junit_xml_file> | junit2json to-xml > junit.xml
+//! ```
+//!
 //! # Notice
 //! junit2json-rs has some major changes from ts-junit2json.
 //! Most of the changes are to compliant with the JUnit XML Schema.
 //!
 //! - A `testsuites` or `testsuite` key appears in the root of JSON.
 //! - `properties` has `property` array. ts-junit2json has `property` array of object directly.
-//! - `skipped`, `error`, `failure` are object, not array of object.
+//! - `skipped`, `error`, `failure` are object, not array of object. Use `--detail-shape array` to keep all of them as array.
 //! - If XML has undefined tag, it will be ignored. ts-junit2json will be converted to JSON if possible.
+//!   Use `--lossless` to keep undefined attributes and tags in `extra` instead.
 //!
 //! Referenced JUnit XML Schema:
 //! - <https://llg.cubic.org/docs/junit/>
 //! - <https://github.com/testmoapp/junitxml/tree/main>
 //!
+//! # Migrating from 0.2
+//! 0.3.0 has a breaking change of the library API. The JSON output of the CLI is not changed.
+//!
+//! - `TestCase::skipped`, `TestCase::error` and `TestCase::failure` are `Option<Vec<Detail>>` instead of `Option<Detail>`, to keep every element of each kind.
+//!   Use `.first()` to get the element that 0.2 kept.
+//! - Serializing them with serde outputs all of them as an array.
+//!   Use `to_json_value(&DetailShape::Object)` to output the first element as an object like 0.2.
+//!
 //! # WASI
 //! junit2json-rs also provides WASI executable.
 //!
 //! If you have wasm runtime (ex. wasmtime), you can execute `junit2json.wasm` that can download from [GitHub Releases](https://github.com/Kesin11/junit2json-rs/releases) instead of native binary.
 //!
-//! ```
+//! ```shell
 //! wasmtime junit2json.wasm --dir=. -- -p <junit_xml_file>
 //! ```
 //!
 
-use cli::PossibleFilterTags;
+use cli::{DetailShape, PossibleFilterTags};
 use quick_xml::de;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_with::skip_serializing_none;
 use std::default;
-use std::io;
+use std::io::{self, Read};
 
 pub mod cli;
+mod counters;
+mod error;
+mod extra;
+mod filter;
+mod filter_tags;
+mod git;
+mod json;
+mod lenient;
+mod merge;
+mod metadata;
+mod redact;
+mod schema;
+mod shape;
+mod stream;
+mod strip;
+mod truncate;
+mod xml;
+
+pub use counters::CounterMismatch;
+pub use error::{Error, Location, Warning};
+pub use filter::TestCaseFilter;
+pub use filter_tags::FilterTag;
+pub use git::GitInfo;
+pub use json::{from_json_reader, from_json_str, from_json_value};
+pub use merge::MergeOptions;
+pub use metadata::{add_metadata, detect_ci_metadata};
+pub use redact::{RedactOptions, RedactReport, REDACTED};
+pub use schema::{
+    bigquery_flat_schema, bigquery_schema, bigquery_schema_with_metadata, json_flat_schema,
+    json_schema, json_schema_with_metadata, BigQueryField,
+};
+pub use stream::{FlatTestCase, SuiteContext, TestCaseStream};
+pub use truncate::TruncateOptions;
+pub use xml::{to_string, to_writer};
 
 fn trim_default_items<T: default::Default + PartialEq + Clone>(vec: &mut Option<Vec<T>>) {
-    match vec {
-        Some(v) => {
-            *vec = v
-                .iter()
-                .filter(|&item| item != &Default::default())
-                .cloned()
-                .collect::<Vec<_>>()
-                .into();
-        }
-        None => {}
+    if let Some(v) = vec {
+        *vec = v
+            .iter()
+            .filter(|&item| item != &Default::default())
+            .cloned()
+            .collect::<Vec<_>>()
+            .into();
     }
 }
 
@@ -183,13 +355,19 @@ fn trim_default_items<T: default::Default + PartialEq + Clone>(vec: &mut Option<
 /// <testsuite name="testsuite1" tests=1 time=0.1>
 /// </testsuite>
 /// ```
-#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, PartialEq, Clone)]
 #[serde(rename_all = "lowercase")]
 pub enum TestSuitesOrTestSuite {
     TestSuites(TestSuites),
     TestSuite(Box<TestSuite>),
 }
 impl TestSuitesOrTestSuite {
+    pub fn trim_empty_items(&mut self) {
+        match self {
+            TestSuitesOrTestSuite::TestSuites(ref mut testsuites) => testsuites.trim_empty_items(),
+            TestSuitesOrTestSuite::TestSuite(ref mut testsuite) => testsuite.trim_empty_items(),
+        }
+    }
     /// Remove all `system-out` and `system-err` from each `testsuite` and `testcase`.
     ///
     /// # Examples
@@ -226,6 +404,108 @@ impl TestSuitesOrTestSuite {
             }
         }
     }
+
+    /// Set `outcome` of every testcase, so that it is output in the JSON. See [`TestCase::outcome`].
+    ///
+    /// # Examples
+    /// ```
+    /// use junit2json;
+    ///
+    /// let xml = r#"
+    ///   <testsuite name="suite1">
+    ///       <testcase name="case1"><skipped /></testcase>
+    ///   </testsuite>
+    /// "#;
+    /// let mut testsuite = junit2json::from_str(xml).unwrap();
+    /// testsuite.set_outcomes();
+    /// let json = testsuite.to_json_value(&junit2json::cli::DetailShape::Object).unwrap();
+    /// assert_eq!(json["testsuite"]["testcase"][0]["outcome"], "skipped");
+    /// ```
+    pub fn set_outcomes(&mut self) {
+        match self {
+            TestSuitesOrTestSuite::TestSuites(ref mut testsuites) => testsuites.set_outcomes(),
+            TestSuitesOrTestSuite::TestSuite(ref mut testsuite) => testsuite.set_outcomes(),
+        }
+    }
+
+    /// Returns all testcases that passed only after retry. See [`TestCase::is_flaky`].
+    ///
+    /// # Examples
+    /// ```
+    /// use junit2json;
+    ///
+    /// let xml = r#"
+    ///   <?xml version="1.0" encoding="UTF-8"?>
+    ///   <testsuite name="suite1">
+    ///       <testcase name="case1">
+    ///         <flakyFailure message="failed at first run" />
+    ///       </testcase>
+    ///       <testcase name="case2" />
+    ///   </testsuite>
+    /// "#;
+    /// let testsuites = junit2json::from_str(xml).unwrap();
+    /// let flaky = testsuites.flaky_testcases();
+    /// assert_eq!(flaky.len(), 1);
+    /// assert_eq!(flaky[0].name, Some("case1".to_string()));
+    /// ```
+    pub fn flaky_testcases(&self) -> Vec<&TestCase> {
+        let mut flaky = vec![];
+        match self {
+            TestSuitesOrTestSuite::TestSuites(testsuites) => {
+                if let Some(testsuite) = &testsuites.testsuite {
+                    testsuite
+                        .iter()
+                        .for_each(|item| item.collect_flaky_testcases(&mut flaky));
+                }
+            }
+            TestSuitesOrTestSuite::TestSuite(testsuite) => {
+                testsuite.collect_flaky_testcases(&mut flaky);
+            }
+        }
+        flaky
+    }
+
+    /// Convert to `serde_json::Value` with the given shape of `skipped`, `error` and `failure`.
+    ///
+    /// [`cli::DetailShape::Object`] keeps only the first element of each kind,
+    /// that is compatible with the JSON produced by previous versions.
+    /// [`cli::DetailShape::Array`] keeps all elements as an array.
+    /// Serializing with serde directly keeps all elements like [`cli::DetailShape::Array`].
+    ///
+    /// # Examples
+    /// ```
+    /// use junit2json;
+    ///
+    /// let xml = r#"
+    ///   <?xml version="1.0" encoding="UTF-8"?>
+    ///   <testsuite name="suite1">
+    ///       <testcase name="case1">
+    ///         <failure message="failure1" />
+    ///         <failure message="failure2" />
+    ///       </testcase>
+    ///   </testsuite>
+    /// "#;
+    /// let testsuites = junit2json::from_str(xml).unwrap();
+    /// let json = testsuites.to_json_value(&junit2json::cli::DetailShape::Array).unwrap();
+    /// println!("{}", json);
+    /// ```
+    pub fn to_json_value(
+        &self,
+        shape: &DetailShape,
+    ) -> Result<serde_json::Value, serde_json::Error> {
+        to_json_value_exact(self, shape)
+    }
+}
+
+/// Convert to `serde_json::Value` through a JSON string, because `serde_json::to_value` widens f32 to f64,
+/// which turns `time: 0.1` into `time: 0.10000000149011612`.
+///
+/// `skipped`, `error` and `failure` are serialized in `shape` while converting.
+fn to_json_value_exact<T: Serialize>(
+    value: &T,
+    shape: &DetailShape,
+) -> Result<serde_json::Value, serde_json::Error> {
+    serde_json::from_str(&serde_json::to_string(&shape::Shaped::new(value, *shape))?)
 }
 
 /// It corresponds to `<testsuites>`
@@ -235,7 +515,7 @@ impl TestSuitesOrTestSuite {
 /// </testsuites>
 /// ```
 #[skip_serializing_none]
-#[derive(Serialize, Deserialize, Debug, Default, PartialEq)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Default, PartialEq, Clone)]
 pub struct TestSuites {
     #[serde(rename(deserialize = "@name"))]
     pub name: Option<String>,
@@ -247,22 +527,33 @@ pub struct TestSuites {
     pub failures: Option<u32>,
     #[serde(rename(deserialize = "@errors"))]
     pub errors: Option<u32>,
+    #[serde(rename(deserialize = "@skipped"))]
+    pub skipped: Option<u32>,
+    #[serde(rename(deserialize = "@assertions"))]
+    pub assertions: Option<u32>,
+    #[serde(rename(deserialize = "@timestamp"))]
+    pub timestamp: Option<String>,
 
     pub testsuite: Option<Vec<TestSuite>>,
+
+    /// Unrecognized attributes and child elements. Only collected in the lossless mode, see [`ParseOptions::lossless`].
+    #[serde(skip_deserializing)]
+    pub extra: Option<serde_json::Map<String, serde_json::Value>>,
 }
 impl TestSuites {
     pub fn trim_empty_items(&mut self) {
-        match &mut self.testsuite {
-            Some(testsuite) => testsuite
+        if let Some(testsuite) = &mut self.testsuite {
+            testsuite
                 .iter_mut()
-                .for_each(|item| item.trim_empty_items()),
-            None => {}
+                .for_each(|item| item.trim_empty_items());
         }
     }
     pub fn filter_tags(&mut self, tags: &[PossibleFilterTags]) {
-        match &mut self.testsuite {
-            Some(testsuite) => testsuite.iter_mut().for_each(|item| item.filter_tags(tags)),
-            None => {}
+        self.remove_fields(&tags.iter().map(FilterTag::from).collect::<Vec<_>>());
+    }
+    pub fn set_outcomes(&mut self) {
+        if let Some(testsuite) = &mut self.testsuite {
+            testsuite.iter_mut().for_each(|item| item.set_outcomes());
         }
     }
 }
@@ -274,7 +565,7 @@ impl TestSuites {
 /// </testsuite>
 /// ```
 #[skip_serializing_none]
-#[derive(Serialize, Deserialize, Debug, Default, PartialEq)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Default, PartialEq, Clone)]
 pub struct TestSuite {
     #[serde(rename(deserialize = "@name"))]
     pub name: Option<String>,
@@ -313,36 +604,54 @@ pub struct TestSuite {
     pub system_err: Option<Vec<String>>,
     pub properties: Option<Properties>,
     pub testcase: Option<Vec<TestCase>>,
+    /// Nested `<testsuite>` elements, emitted by tools like Maven Surefire aggregated reports, PHPUnit or Ant's junitreport.
+    pub testsuite: Option<Vec<TestSuite>>,
+
+    /// Unrecognized attributes and child elements. Only collected in the lossless mode, see [`ParseOptions::lossless`].
+    #[serde(skip_deserializing)]
+    pub extra: Option<serde_json::Map<String, serde_json::Value>>,
 }
 impl TestSuite {
     pub fn trim_empty_items(&mut self) {
         trim_default_items(&mut self.system_out);
         trim_default_items(&mut self.system_err);
 
-        match &mut self.properties {
-            Some(properties) => {
-                properties.trim_empty_items();
-                if properties.property.is_none() {
-                    self.properties = None;
-                }
+        if let Some(properties) = &mut self.properties {
+            properties.trim_empty_items();
+            if properties.property.is_none() {
+                self.properties = None;
             }
-            None => {}
         }
-        match &mut self.testcase {
-            Some(testcase) => testcase.iter_mut().for_each(|item| item.trim_empty_items()),
-            None => {}
+        if let Some(testcase) = &mut self.testcase {
+            testcase.iter_mut().for_each(|item| item.trim_empty_items());
+        }
+        if let Some(testsuite) = &mut self.testsuite {
+            testsuite
+                .iter_mut()
+                .for_each(|item| item.trim_empty_items());
         }
     }
     pub fn filter_tags(&mut self, tags: &[PossibleFilterTags]) {
-        for tag in tags.iter() {
-            match tag {
-                PossibleFilterTags::SystemOut => self.system_out = None,
-                PossibleFilterTags::SystemErr => self.system_err = None,
-            }
+        self.remove_fields(&tags.iter().map(FilterTag::from).collect::<Vec<_>>());
+    }
+    pub fn set_outcomes(&mut self) {
+        if let Some(testcase) = &mut self.testcase {
+            testcase
+                .iter_mut()
+                .for_each(|item| item.outcome = Some(item.outcome()));
         }
-        match &mut self.testcase {
-            Some(testcase) => testcase.iter_mut().for_each(|item| item.filter_tags(tags)),
-            None => {}
+        if let Some(testsuite) = &mut self.testsuite {
+            testsuite.iter_mut().for_each(|item| item.set_outcomes());
+        }
+    }
+    fn collect_flaky_testcases<'a>(&'a self, flaky: &mut Vec<&'a TestCase>) {
+        if let Some(testcase) = &self.testcase {
+            flaky.extend(testcase.iter().filter(|item| item.is_flaky()));
+        }
+        if let Some(testsuite) = &self.testsuite {
+            testsuite
+                .iter()
+                .for_each(|item| item.collect_flaky_testcases(flaky));
         }
     }
 }
@@ -354,7 +663,7 @@ impl TestSuite {
 /// </testcase>
 /// ```
 #[skip_serializing_none]
-#[derive(Serialize, Deserialize, Debug, Default, PartialEq)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Default, PartialEq, Clone)]
 pub struct TestCase {
     #[serde(rename(deserialize = "@name"))]
     pub name: Option<String>,
@@ -370,41 +679,145 @@ pub struct TestCase {
     pub file: Option<String>,
     #[serde(rename(deserialize = "@line"))]
     pub line: Option<u32>,
+    #[serde(rename(deserialize = "@timestamp"))]
+    pub timestamp: Option<String>,
+    #[serde(rename(deserialize = "@hostname"))]
+    pub hostname: Option<String>,
 
     #[serde(rename = "system-out")]
     pub system_out: Option<Vec<String>>,
     #[serde(rename = "system-err")]
     pub system_err: Option<Vec<String>>,
-    pub skipped: Option<Detail>,
-    pub error: Option<Detail>,
-    pub failure: Option<Detail>,
+    pub properties: Option<Properties>,
+    pub skipped: Option<Vec<Detail>>,
+    pub error: Option<Vec<Detail>>,
+    pub failure: Option<Vec<Detail>>,
+    #[serde(rename = "flakyFailure")]
+    pub flaky_failure: Option<Vec<Rerun>>,
+    #[serde(rename = "flakyError")]
+    pub flaky_error: Option<Vec<Rerun>>,
+    #[serde(rename = "rerunFailure")]
+    pub rerun_failure: Option<Vec<Rerun>>,
+    #[serde(rename = "rerunError")]
+    pub rerun_error: Option<Vec<Rerun>>,
+
+    /// Result of the testcase. Only set by [`TestSuitesOrTestSuite::set_outcomes`], see [`TestCase::outcome`].
+    #[serde(skip_deserializing)]
+    pub outcome: Option<Outcome>,
+
+    /// Unrecognized attributes and child elements. Only collected in the lossless mode, see [`ParseOptions::lossless`].
+    #[serde(skip_deserializing)]
+    pub extra: Option<serde_json::Map<String, serde_json::Value>>,
 }
 impl TestCase {
     pub fn trim_empty_items(&mut self) {
         trim_default_items(&mut self.system_out);
         trim_default_items(&mut self.system_err);
+
+        if let Some(properties) = &mut self.properties {
+            properties.trim_empty_items();
+            if properties.property.is_none() {
+                self.properties = None;
+            }
+        }
+        [
+            &mut self.flaky_failure,
+            &mut self.flaky_error,
+            &mut self.rerun_failure,
+            &mut self.rerun_error,
+        ]
+        .into_iter()
+        .flatten()
+        .flatten()
+        .for_each(|rerun| rerun.trim_empty_items());
     }
     pub fn filter_tags(&mut self, tags: &[PossibleFilterTags]) {
-        for tag in tags.iter() {
-            match tag {
-                PossibleFilterTags::SystemOut => self.system_out = None,
-                PossibleFilterTags::SystemErr => self.system_err = None,
+        self.remove_fields(&tags.iter().map(FilterTag::from).collect::<Vec<_>>());
+    }
+    /// Whether the testcase passed only after retry.
+    ///
+    /// It has `<flakyFailure>` or `<flakyError>` but neither `<failure>` nor `<error>`.
+    pub fn is_flaky(&self) -> bool {
+        (self.flaky_failure.is_some() || self.flaky_error.is_some())
+            && self.failure.is_none()
+            && self.error.is_none()
+    }
+    /// Result of the testcase derived from its children and `status` attribute.
+    ///
+    /// `<failure>` and `<error>` take precedence over everything.
+    /// `status` like `disabled` and `notrun` of GoogleTest means [`Outcome::Disabled`],
+    /// then `<skipped>` means [`Outcome::Skipped`] and [`TestCase::is_flaky`] means [`Outcome::Flaky`].
+    /// Otherwise `status` like `failed`, `error` and `skipped` is used, and everything else is [`Outcome::Passed`].
+    ///
+    /// # Examples
+    /// ```
+    /// use junit2json;
+    ///
+    /// let xml = r#"
+    ///   <testsuite name="suite1">
+    ///       <testcase name="case1"><failure message="failed" /></testcase>
+    ///       <testcase name="case2" status="notrun" />
+    ///   </testsuite>
+    /// "#;
+    /// let junit2json::TestSuitesOrTestSuite::TestSuite(testsuite) = junit2json::from_str(xml).unwrap() else {
+    ///     panic!("not testsuite");
+    /// };
+    /// let testcase = testsuite.testcase.unwrap();
+    /// assert_eq!(testcase[0].outcome(), junit2json::Outcome::Failed);
+    /// assert_eq!(testcase[1].outcome(), junit2json::Outcome::Disabled);
+    /// ```
+    pub fn outcome(&self) -> Outcome {
+        let status = self.status.as_deref().unwrap_or_default().to_lowercase();
+        if self.failure.is_some() {
+            Outcome::Failed
+        } else if self.error.is_some() {
+            Outcome::Errored
+        } else if matches!(status.as_str(), "disabled" | "notrun") {
+            Outcome::Disabled
+        } else if self.skipped.is_some() {
+            Outcome::Skipped
+        } else if self.is_flaky() {
+            Outcome::Flaky
+        } else {
+            match status.as_str() {
+                "fail" | "failed" | "failure" => Outcome::Failed,
+                "error" | "errored" => Outcome::Errored,
+                "skip" | "skipped" | "ignored" | "pending" => Outcome::Skipped,
+                _ => Outcome::Passed,
             }
         }
     }
 }
 
+/// Result of a testcase. See [`TestCase::outcome`].
+///
+/// `Flaky` means that the testcase passed only after retry.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum Outcome {
+    Passed,
+    Failed,
+    Errored,
+    Skipped,
+    Disabled,
+    Flaky,
+}
+
 /// It corresponds to `<skipped>, <error>, <failure>`
 ///
+/// A testcase can have multiple elements of each kind, so `TestCase` keeps all of them as `Vec<Detail>`.
+/// How they appear in JSON is decided by [`cli::DetailShape`].
+///
 /// ```xml
 /// <testcase>
 ///    <skipped message="foo" type="bar">Skipped</skipped>
 ///    <error message="foo" type="bar">Error</error>
 ///    <failure message="foo" type="bar">Failure</failure>
+///    <failure message="foo2" type="bar2">Failure2</failure>
 /// </testcase>
 /// ```
 #[skip_serializing_none]
-#[derive(Serialize, Deserialize, Debug, Default, PartialEq)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Default, PartialEq, Clone)]
 pub struct Detail {
     #[serde(rename(deserialize = "@message"))]
     pub message: Option<String>,
@@ -412,6 +825,50 @@ pub struct Detail {
     pub r#type: Option<String>,
     #[serde(rename(deserialize = "$value"))]
     pub inner: Option<String>,
+
+    /// Unrecognized attributes and child elements. Only collected in the lossless mode, see [`ParseOptions::lossless`].
+    #[serde(skip_deserializing)]
+    pub extra: Option<serde_json::Map<String, serde_json::Value>>,
+}
+
+/// It corresponds to `<flakyFailure>, <flakyError>, <rerunFailure>, <rerunError>`
+///
+/// Maven Surefire and cargo-nextest write them for each retry of a testcase.
+/// `flaky*` means that the testcase passed after retry, `rerun*` means that the retry also failed.
+///
+/// ```xml
+/// <testcase>
+///    <flakyFailure message="foo" type="bar">
+///        <stackTrace>stack trace</stackTrace>
+///        <system-out>system out text</system-out>
+///        <system-err>system error text</system-err>
+///    </flakyFailure>
+/// </testcase>
+/// ```
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Default, PartialEq, Clone)]
+pub struct Rerun {
+    #[serde(rename(deserialize = "@message"))]
+    pub message: Option<String>,
+    #[serde(rename(deserialize = "@type"))]
+    pub r#type: Option<String>,
+    #[serde(rename(deserialize = "@timestamp"))]
+    pub timestamp: Option<String>,
+    #[serde(rename(deserialize = "@time"))]
+    pub time: Option<f32>,
+
+    #[serde(rename = "stackTrace")]
+    pub stack_trace: Option<String>,
+    #[serde(rename = "system-out")]
+    pub system_out: Option<Vec<String>>,
+    #[serde(rename = "system-err")]
+    pub system_err: Option<Vec<String>>,
+}
+impl Rerun {
+    pub fn trim_empty_items(&mut self) {
+        trim_default_items(&mut self.system_out);
+        trim_default_items(&mut self.system_err);
+    }
 }
 
 /// It corresponds to `<properties>`
@@ -422,7 +879,7 @@ pub struct Detail {
 /// </properties>
 /// ```
 #[skip_serializing_none]
-#[derive(Serialize, Deserialize, Debug, Default, PartialEq)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Default, PartialEq, Clone)]
 pub struct Properties {
     pub property: Option<Vec<Property>>,
 }
@@ -438,7 +895,7 @@ impl Properties {
 /// <property name="foo" value="bar" />
 /// ```
 #[skip_serializing_none]
-#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Clone)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Default, PartialEq, Clone)]
 pub struct Property {
     #[serde(rename(deserialize = "@name"))]
     pub name: Option<String>,
@@ -467,16 +924,27 @@ pub struct Property {
 /// });
 /// println!("{:#?}", testsuites);
 /// ```
-pub fn from_reader<T>(reader: io::BufReader<T>) -> Result<TestSuitesOrTestSuite, quick_xml::DeError>
+pub fn from_reader<T>(reader: io::BufReader<T>) -> Result<TestSuitesOrTestSuite, Error>
 where
     T: io::Read,
 {
-    let mut root: TestSuitesOrTestSuite = de::from_reader(reader)?;
-    match root {
-        TestSuitesOrTestSuite::TestSuites(ref mut testsuites) => testsuites.trim_empty_items(),
-        TestSuitesOrTestSuite::TestSuite(ref mut testsuite) => testsuite.trim_empty_items(),
-    }
-    Ok(root)
+    from_reader_with_options(reader, &ParseOptions::default())
+}
+
+/// Deserialize JUnit XML from any reader like `File`, `io::Stdin` or `&[u8]`.
+///
+/// Unlike [`from_reader`], the reader does not need to be wrapped in `io::BufReader`.
+///
+/// # Examples
+/// ```
+/// use junit2json;
+///
+/// let xml = br#"<testsuite name="suite1"><testcase name="case1" /></testsuite>"#;
+/// let testsuite = junit2json::from_read(&xml[..]).unwrap();
+/// println!("{:#?}", testsuite);
+/// ```
+pub fn from_read<R: io::Read>(reader: R) -> Result<TestSuitesOrTestSuite, Error> {
+    from_reader(io::BufReader::new(reader))
 }
 
 /// Deserialize JUnit XML from a string.
@@ -499,6 +967,182 @@ where
 /// });
 /// println!("{:#?}", testsuites);
 /// ```
-pub fn from_str(s: &str) -> Result<TestSuitesOrTestSuite, quick_xml::DeError> {
-    from_reader(io::BufReader::new(s.as_bytes()))
+pub fn from_str(s: &str) -> Result<TestSuitesOrTestSuite, Error> {
+    from_str_with_options(s, &ParseOptions::default())
+}
+
+/// Options for [`from_reader_with_options`] and [`from_str_with_options`].
+#[derive(Debug, Default, Clone)]
+pub struct ParseOptions {
+    /// Keep unrecognized attributes and child elements in `extra` of `TestSuites`, `TestSuite`, `TestCase` and `Detail`
+    /// instead of ignoring them.
+    ///
+    /// Attributes are stored as string with `@` prefix like `"@uuid"`.
+    /// Child elements are stored as array, and each element becomes a string of its text
+    /// or an object of its attributes, children and `"$text"`.
+    pub lossless: bool,
+}
+
+/// Deserialize JUnit XML from a reader with [`ParseOptions`].
+///
+/// The XML is deserialized while it is read, so it is not held in memory.
+/// Errors have the element path like [`from_str_with_options`], but problems that only serde finds are reported
+/// at the element that was read last, which can be a little after the actual problem.
+/// The lossless mode reads all into memory, because it walks the XML again to collect the unrecognized items.
+///
+/// # Examples
+/// ```
+/// use junit2json;
+/// use std::fs::File;
+/// use std::io::BufReader;
+///
+/// let file = File::open("tests/fixtures/cargo-nextest.xml").unwrap();
+/// let options = junit2json::ParseOptions {
+///     lossless: true,
+/// };
+/// let testsuites = junit2json::from_reader_with_options(BufReader::new(file), &options).unwrap();
+/// println!("{:#?}", testsuites);
+/// ```
+pub fn from_reader_with_options<T>(
+    mut reader: io::BufReader<T>,
+    options: &ParseOptions,
+) -> Result<TestSuitesOrTestSuite, Error>
+where
+    T: io::Read,
+{
+    if options.lossless {
+        let mut xml = String::new();
+        reader.read_to_string(&mut xml)?;
+        return from_str_with_options(&xml, options);
+    }
+    let mut deserializer = de::Deserializer::from_reader(error::CheckedReader::new(reader));
+    let mut root = TestSuitesOrTestSuite::deserialize(&mut deserializer)
+        .map_err(|err| deserializer.get_ref().get_ref().get_ref().error(err))?;
+    root.trim_empty_items();
+    Ok(root)
+}
+
+/// Deserialize JUnit XML from a string with [`ParseOptions`].
+///
+/// # Examples
+/// ```
+/// use junit2json;
+///
+/// let xml = r#"
+///     <?xml version="1.0" encoding="UTF-8"?>
+///     <testsuites uuid="d241b926-80a6-45cd-bc50-537917d0f60b">
+///         <testsuite failures="1" tests="2">
+///         </testsuite>
+///     </testsuites>
+/// "#;
+/// let options = junit2json::ParseOptions {
+///     lossless: true,
+/// };
+/// let testsuites = junit2json::from_str_with_options(xml, &options).unwrap();
+/// println!("{:#?}", testsuites);
+/// ```
+pub fn from_str_with_options(
+    s: &str,
+    options: &ParseOptions,
+) -> Result<TestSuitesOrTestSuite, Error> {
+    let mut deserializer = de::Deserializer::from_str(s);
+    let mut root = TestSuitesOrTestSuite::deserialize(&mut deserializer).map_err(|err| {
+        let position = deserializer.get_ref().get_ref().buffer_position();
+        error::locate(s, err, position)
+    })?;
+    root.trim_empty_items();
+    if options.lossless {
+        extra::apply(&mut root, extra::collect(s)?);
+    }
+    Ok(root)
+}
+
+/// Deserialize JUnit XML from a string, recovering from malformed or truncated XML.
+///
+/// It is useful when a CI job is killed while writing the report.
+/// - Numeric attributes that can not be parsed, like `time="N/A"`, become `None`.
+/// - When the XML ends unexpectedly or has a syntax error, every complete `<testcase>` and `<testsuite>` before it is kept.
+///
+/// Each recovered problem is returned as [`Warning`].
+///
+/// # Examples
+/// ```
+/// use junit2json;
+///
+/// let xml = r#"
+///     <?xml version="1.0" encoding="UTF-8"?>
+///     <testsuites>
+///         <testsuite name="suite1" time="N/A">
+///             <testcase name="case1" />
+///             <testcase name="case2">
+/// "#;
+/// let (testsuites, warnings) = junit2json::from_str_lenient(xml, &junit2json::ParseOptions::default()).unwrap();
+/// for warning in warnings {
+///     eprintln!("warning: {}", warning);
+/// }
+/// println!("{:#?}", testsuites);
+/// ```
+pub fn from_str_lenient(
+    s: &str,
+    options: &ParseOptions,
+) -> Result<(TestSuitesOrTestSuite, Vec<Warning>), Error> {
+    let (xml, warnings) = lenient::repair(s)?;
+    let root = from_str_with_options(&xml, options)?;
+    Ok((root, warnings))
+}
+
+/// Deserialize JUnit XML from a reader, recovering from malformed or truncated XML.
+/// See [`from_str_lenient`] for details.
+///
+/// # Examples
+/// ```
+/// use junit2json;
+/// use std::fs::File;
+/// use std::io::BufReader;
+///
+/// let file = File::open("tests/fixtures/cargo-nextest.xml").unwrap();
+/// let (testsuites, warnings) =
+///     junit2json::from_reader_lenient(BufReader::new(file), &junit2json::ParseOptions::default()).unwrap();
+/// assert!(warnings.is_empty());
+/// println!("{:#?}", testsuites);
+/// ```
+pub fn from_reader_lenient<T>(
+    mut reader: io::BufReader<T>,
+    options: &ParseOptions,
+) -> Result<(TestSuitesOrTestSuite, Vec<Warning>), Error>
+where
+    T: io::Read,
+{
+    let mut xml = String::new();
+    reader.read_to_string(&mut xml)?;
+    from_str_lenient(&xml, options)
+}
+
+/// Stream testcases of JUnit XML from a reader one by one, without building the whole [`TestSuitesOrTestSuite`].
+///
+/// Each item is a testcase with its [`SuiteContext`] that has the attributes and `properties` of
+/// the enclosing `<testsuites>` and `<testsuite>`. The context is shared by the testcases of the same testsuite.
+/// Memory usage is bounded by the size of one `<testcase>`, or one text like `<system-out>` of a testsuite
+/// that is skipped, so it is suitable for huge reports.
+///
+/// `<properties>` of a `<testsuite>` are in the context of the testcases after them only.
+/// When `<properties>` come after `<testcase>` elements, the testcases already yielded don't have them.
+///
+/// # Examples
+/// ```
+/// use junit2json;
+/// use std::fs::File;
+/// use std::io::BufReader;
+///
+/// let file = File::open("tests/fixtures/cargo-nextest.xml").unwrap();
+/// for item in junit2json::stream_from_reader(BufReader::new(file)) {
+///     let (context, testcase) = item.unwrap();
+///     println!("{:?} {:?}", context.parent().and_then(|suite| suite.name.as_ref()), testcase.name);
+/// }
+/// ```
+pub fn stream_from_reader<T>(reader: io::BufReader<T>) -> TestCaseStream<io::BufReader<T>>
+where
+    T: io::Read,
+{
+    TestCaseStream::new(reader)
 }
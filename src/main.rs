@@ -2,42 +2,371 @@ use clap::Parser;
 
 use junit2json::cli;
 use std::fs::File;
-use std::io::BufReader;
+use std::io::{self, BufReader, Write};
+use std::path::Path;
 use std::process;
+use std::sync::Arc;
 
 fn main() {
     let args = cli::Args::parse();
 
     // println!("{:?}", args);
-    let file = File::open(&args.path).unwrap_or_else(|msg| {
-        eprintln!("File::open error: {}", msg);
+    match &args.command {
+        Some(cli::Command::Schema(schema_args)) => {
+            schema(schema_args);
+            return;
+        }
+        Some(cli::Command::ToXml(to_xml_args)) => {
+            to_xml(to_xml_args);
+            return;
+        }
+        Some(cli::Command::Merge(merge_args)) => {
+            merge(merge_args);
+            return;
+        }
+        None => {}
+    }
+    let files = args.input_files().unwrap_or_else(|msg| {
+        eprintln!("input path error: {}", msg);
         process::exit(1);
     });
-    let reader = BufReader::new(file);
-    let mut testsuites = junit2json::from_reader(reader).unwrap_or_else(|msg| {
-        eprintln!("junit2json::from_reader error: {}", msg);
+    let tags_path = args.tags_path(&files);
+    let mut out = io::BufWriter::new(io::stdout().lock());
+    let mut values = vec![];
+    let mut report = junit2json::RedactReport::default();
+    let git = args.git_info().unwrap_or_else(|msg| {
+        eprintln!("git error: {}", msg);
         process::exit(1);
     });
-    // println!("{:#?}", testsuites);
+    let metadata = args.metadata(git.as_ref());
+    for path in &files {
+        let reader = open(path);
+        let source = tags_path.then_some(path.as_path());
+        if args.stream || args.flat {
+            stream(
+                reader,
+                source,
+                &args,
+                git.as_ref(),
+                &metadata,
+                &mut out,
+                &mut report,
+            );
+            continue;
+        }
+        let mut value = convert(reader, source, &args, git.as_ref(), &mut report);
+        junit2json::add_metadata(&mut value, &metadata);
+        match args.output {
+            cli::OutputMode::Array => values.push(value),
+            cli::OutputMode::Ndjson => write_json(&mut out, &value, false),
+            cli::OutputMode::Document => write_json(&mut out, &value, args.pretty),
+        }
+    }
+    if let cli::OutputMode::Array = args.output {
+        if !(args.stream || args.flat) {
+            write_json(&mut out, &serde_json::Value::Array(values), args.pretty);
+        }
+    }
+    out.flush().unwrap_or_else(|msg| {
+        eprintln!("write error: {}", msg);
+        process::exit(1);
+    });
+    if args.redact_options().is_some() {
+        eprintln!("redacted {}", report);
+    }
+}
 
-    // Filter tags
-    if let Some(tags) = args.filter_tags {
-        if !tags.is_empty() {
-            testsuites.filter_tags(&tags);
+/// Open the file, or stdin when the path is [`cli::STDIN`].
+fn open(path: &Path) -> BufReader<Box<dyn io::Read>> {
+    match path.as_os_str() == cli::STDIN {
+        true => BufReader::new(Box::new(io::stdin().lock())),
+        false => BufReader::new(Box::new(File::open(path).unwrap_or_else(|msg| {
+            eprintln!("File::open error: {}: {}", path.display(), msg);
+            process::exit(1);
+        }))),
+    }
+}
+
+/// Insert "path" of the source file as the first key of the JSON object.
+fn tag_path(value: serde_json::Value, path: Option<&Path>) -> serde_json::Value {
+    match (value, path) {
+        (serde_json::Value::Object(object), Some(path)) => {
+            let mut tagged = serde_json::Map::new();
+            tagged.insert("path".to_string(), path.display().to_string().into());
+            tagged.extend(object);
+            serde_json::Value::Object(tagged)
         }
+        (value, _) => value,
     }
+}
 
+fn write_json(out: &mut impl Write, value: &serde_json::Value, pretty: bool) {
     // Convert to JSON string
-    let json = match args.pretty {
-        true => serde_json::to_string_pretty(&testsuites).unwrap_or_else(|msg| {
+    let json = match pretty {
+        true => serde_json::to_string_pretty(value).unwrap_or_else(|msg| {
             eprintln!("serde_json::to_string_pretty error: {}", msg);
             process::exit(1);
         }),
-        false => serde_json::to_string(&testsuites).unwrap_or_else(|msg| {
+        false => serde_json::to_string(value).unwrap_or_else(|msg| {
             eprintln!("serde_json::to_string error: {}", msg);
             process::exit(1);
         }),
     };
+    writeln!(out, "{}", json).unwrap_or_else(|msg| {
+        eprintln!("write error: {}", msg);
+        process::exit(1);
+    });
+}
+
+fn convert(
+    reader: BufReader<impl io::Read>,
+    source: Option<&Path>,
+    args: &cli::Args,
+    git: Option<&junit2json::GitInfo>,
+    report: &mut junit2json::RedactReport,
+) -> serde_json::Value {
+    let prefix = source
+        .map(|path| format!("{}: ", path.display()))
+        .unwrap_or_default();
+    let options = junit2json::ParseOptions {
+        lossless: args.lossless,
+    };
+    let mut testsuites = match args.lenient {
+        true => {
+            let (testsuites, warnings) = junit2json::from_reader_lenient(reader, &options)
+                .unwrap_or_else(|msg| {
+                    eprintln!("junit2json::from_reader_lenient error: {}{}", prefix, msg);
+                    process::exit(1);
+                });
+            for warning in warnings {
+                eprintln!("warning: {}{}", prefix, warning);
+            }
+            testsuites
+        }
+        false => junit2json::from_reader_with_options(reader, &options).unwrap_or_else(|msg| {
+            eprintln!("junit2json::from_reader error: {}{}", prefix, msg);
+            process::exit(1);
+        }),
+    };
+    // println!("{:#?}", testsuites);
+
+    // Validate the counters before testcases are filtered
+    if let Some(cli::CountersMode::Warn) = args.counters {
+        for mismatch in testsuites.validate_counters() {
+            eprintln!("warning: {}{}", prefix, mismatch);
+        }
+    }
+    let filter = args.testcase_filter();
+    if !filter.is_empty() {
+        testsuites.filter_testcases(&filter);
+    }
+    if let Some(cli::CountersMode::Fix) = args.counters {
+        testsuites.recompute_counters();
+    }
+    if args.outcome {
+        testsuites.set_outcomes();
+    }
+
+    // Filter tags
+    if let Some(tags) = &args.filter_tags {
+        if !tags.is_empty() {
+            testsuites.remove_fields(tags);
+        }
+    }
+    if let Some(git) = git.filter(|_| args.git_relative_file) {
+        testsuites.relativize_files(git);
+    }
+    if let Some(mode) = &args.strip {
+        testsuites.strip_texts(mode);
+    }
+    if let Some(options) = args.redact_options() {
+        report.merge(testsuites.redact(&options));
+    }
+    if let Some(options) = args.truncate_options() {
+        testsuites.truncate_texts(&options);
+    }
+
+    let value = testsuites
+        .to_json_value(&args.detail_shape)
+        .unwrap_or_else(|msg| {
+            eprintln!("serde_json::to_value error: {}", msg);
+            process::exit(1);
+        });
+    tag_path(value, source)
+}
 
+fn stream(
+    reader: BufReader<impl io::Read>,
+    source: Option<&Path>,
+    args: &cli::Args,
+    git: Option<&junit2json::GitInfo>,
+    metadata: &serde_json::Map<String, serde_json::Value>,
+    out: &mut impl Write,
+    report: &mut junit2json::RedactReport,
+) {
+    let filter = args.testcase_filter();
+    let truncate_options = args.truncate_options();
+    let redact_options = args.redact_options();
+    let relative_git = git.filter(|_| args.git_relative_file);
+    // The enclosing elements are redacted by the stream when they are read, to count each one once
+    let mut stream = junit2json::stream_from_reader(reader);
+    if let Some(options) = &redact_options {
+        stream = stream.redact(options.clone());
+    }
+    // The context of the previous testcase and its prepared one, to filter and resolve each context only once
+    let mut prepared: Option<(Arc<junit2json::SuiteContext>, Arc<junit2json::SuiteContext>)> = None;
+    for item in stream.by_ref() {
+        let (context, mut testcase) = item.unwrap_or_else(|msg| {
+            let prefix = source
+                .map(|path| format!("{}: ", path.display()))
+                .unwrap_or_default();
+            eprintln!("junit2json::stream_from_reader error: {}{}", prefix, msg);
+            process::exit(1);
+        });
+        if !filter.matches(&testcase) {
+            continue;
+        }
+        let context = match prepared.take() {
+            Some((original, done)) if Arc::ptr_eq(&original, &context) => {
+                prepared = Some((original, done.clone()));
+                done
+            }
+            _ => {
+                let mut done = (*context).clone();
+                if let Some(tags) = &args.filter_tags {
+                    done.remove_fields(tags);
+                }
+                if let Some(git) = relative_git {
+                    done.relativize_files(git);
+                }
+                let done = Arc::new(done);
+                prepared = Some((context, done.clone()));
+                done
+            }
+        };
+        if args.outcome {
+            testcase.outcome = Some(testcase.outcome());
+        }
+        if let Some(tags) = &args.filter_tags {
+            testcase.remove_fields(tags);
+        }
+        if let Some(git) = relative_git {
+            testcase.relativize_files(git);
+        }
+        if let Some(mode) = &args.strip {
+            testcase.strip_texts(mode);
+        }
+        if let Some(options) = &redact_options {
+            report.merge(testcase.redact(options));
+        }
+        if let Some(options) = &truncate_options {
+            testcase.truncate_texts(options);
+        }
+        let mut value = match args.flat {
+            true => context.flatten(testcase).to_json_value(&args.detail_shape),
+            false => context.to_json_value(&testcase, &args.detail_shape),
+        }
+        .unwrap_or_else(|msg| {
+            eprintln!("serde_json::to_value error: {}", msg);
+            process::exit(1);
+        });
+        junit2json::add_metadata(&mut value, metadata);
+        writeln!(out, "{}", tag_path(value, source)).unwrap_or_else(|msg| {
+            eprintln!("write error: {}", msg);
+            process::exit(1);
+        });
+    }
+    report.merge(stream.redact_report().clone());
+}
+
+fn schema(args: &cli::SchemaArgs) {
+    let schema = match args.format {
+        cli::SchemaFormat::Bigquery => {
+            let schema = match args.flat {
+                true => junit2json::bigquery_flat_schema(&args.detail_shape),
+                false => junit2json::bigquery_schema(&args.detail_shape),
+            };
+            serde_json::to_value(match args.metadata {
+                true => junit2json::bigquery_schema_with_metadata(schema),
+                false => schema,
+            })
+        }
+        cli::SchemaFormat::JsonSchema => {
+            let schema = match args.flat {
+                true => junit2json::json_flat_schema(&args.detail_shape),
+                false => junit2json::json_schema(&args.detail_shape),
+            };
+            Ok(match args.metadata {
+                true => junit2json::json_schema_with_metadata(schema),
+                false => schema,
+            })
+        }
+    }
+    .unwrap_or_else(|msg| {
+        eprintln!("serde_json::to_value error: {}", msg);
+        process::exit(1);
+    });
+    let json = serde_json::to_string_pretty(&schema).unwrap_or_else(|msg| {
+        eprintln!("serde_json::to_string_pretty error: {}", msg);
+        process::exit(1);
+    });
     println!("{}", json);
 }
+
+fn to_xml(args: &cli::ToXmlArgs) {
+    let testsuites = match args.path.as_deref() {
+        None | Some("-") => junit2json::from_json_reader(BufReader::new(io::stdin().lock())),
+        Some(path) => {
+            let file = File::open(path).unwrap_or_else(|msg| {
+                eprintln!("File::open error: {}", msg);
+                process::exit(1);
+            });
+            junit2json::from_json_reader(BufReader::new(file))
+        }
+    }
+    .unwrap_or_else(|msg| {
+        eprintln!("junit2json::from_json_reader error: {}", msg);
+        process::exit(1);
+    });
+    junit2json::to_writer(io::stdout().lock(), &testsuites).unwrap_or_else(|msg| {
+        eprintln!("junit2json::to_writer error: {}", msg);
+        process::exit(1);
+    });
+}
+
+fn merge(args: &cli::MergeArgs) {
+    let files = args.input_files().unwrap_or_else(|msg| {
+        eprintln!("input path error: {}", msg);
+        process::exit(1);
+    });
+    let options = junit2json::ParseOptions {
+        lossless: args.lossless,
+    };
+    let reports = files.iter().map(|path| {
+        junit2json::from_reader_with_options(open(path), &options).unwrap_or_else(|msg| {
+            eprintln!("junit2json::from_reader error: {}: {}", path.display(), msg);
+            process::exit(1);
+        })
+    });
+    let merge_options = junit2json::MergeOptions {
+        name: args.name.clone(),
+        merge_same_name: args.merge_same_name,
+    };
+    let merged = junit2json::TestSuitesOrTestSuite::TestSuites(
+        junit2json::TestSuitesOrTestSuite::merge(reports, &merge_options),
+    );
+    if args.xml {
+        junit2json::to_writer(io::stdout().lock(), &merged).unwrap_or_else(|msg| {
+            eprintln!("junit2json::to_writer error: {}", msg);
+            process::exit(1);
+        });
+        return;
+    }
+    let value = merged
+        .to_json_value(&args.detail_shape)
+        .unwrap_or_else(|msg| {
+            eprintln!("serde_json::to_value error: {}", msg);
+            process::exit(1);
+        });
+    write_json(&mut io::stdout().lock(), &value, args.pretty);
+}
@@ -0,0 +1,255 @@
+//! Merge many reports, like the ones of sharded CI jobs, into one `<testsuites>`.
+
+use crate::counters::recount_inconsistent;
+use crate::{Properties, TestSuite, TestSuites, TestSuitesOrTestSuite};
+use std::ops::Add;
+
+/// Options for [`TestSuitesOrTestSuite::merge`].
+#[derive(Debug, Default, Clone)]
+pub struct MergeOptions {
+    /// `name` of the merged `<testsuites>`.
+    pub name: Option<String>,
+    /// Merge `<testsuite>` elements with the same name into one,
+    /// like the same test class split into shards.
+    /// Their testcases are concatenated and their counters are summed.
+    pub merge_same_name: bool,
+}
+
+impl TestSuitesOrTestSuite {
+    /// Merge many reports into one `<testsuites>`.
+    ///
+    /// A `<testsuite>` root is wrapped, and `testsuite` of a `<testsuites>` root is appended.
+    /// `tests`, `failures`, `errors`, `skipped`, `assertions` and `time` of the merged `<testsuites>`
+    /// are the sums of each report, and `timestamp` is the earliest one.
+    ///
+    /// The counters of a `<testsuite>` that lacks `tests` or disagrees with its testcases are recounted
+    /// from the testcases first, like [`TestSuitesOrTestSuite::recompute_counters`],
+    /// so that the sums do not miss them.
+    ///
+    /// # Examples
+    /// ```
+    /// use junit2json;
+    ///
+    /// let shard1 = junit2json::from_str(r#"<testsuite name="suite1" tests="1" time="0.1"><testcase name="case1" /></testsuite>"#).unwrap();
+    /// let shard2 = junit2json::from_str(r#"<testsuite name="suite1" tests="1" time="0.2"><testcase name="case2" /></testsuite>"#).unwrap();
+    /// let options = junit2json::MergeOptions {
+    ///     merge_same_name: true,
+    ///     ..Default::default()
+    /// };
+    /// let merged = junit2json::TestSuitesOrTestSuite::merge(vec![shard1, shard2], &options);
+    /// assert_eq!(merged.tests, Some(2));
+    /// assert_eq!(merged.time, Some(0.3));
+    /// assert_eq!(merged.testsuite.unwrap()[0].testcase.as_ref().unwrap().len(), 2);
+    /// ```
+    pub fn merge<I>(reports: I, options: &MergeOptions) -> TestSuites
+    where
+        I: IntoIterator<Item = TestSuitesOrTestSuite>,
+    {
+        let mut merged = TestSuites {
+            name: options.name.clone(),
+            ..Default::default()
+        };
+        let mut testsuite = vec![];
+        for report in reports {
+            let totals = match report {
+                TestSuitesOrTestSuite::TestSuites(mut testsuites) => {
+                    let mut recounted = false;
+                    for item in testsuites.testsuite.iter_mut().flatten() {
+                        recounted |= recount_inconsistent(item);
+                    }
+                    if recounted {
+                        // Sum the recounted testsuites instead of the declared counters
+                        testsuites.tests = None;
+                        testsuites.failures = None;
+                        testsuites.errors = None;
+                        testsuites.skipped = None;
+                    }
+                    let totals = Totals::of_testsuites(&testsuites);
+                    merged.timestamp = earliest(merged.timestamp, testsuites.timestamp);
+                    merged.extra = merge_extra(merged.extra, testsuites.extra);
+                    testsuite.extend(testsuites.testsuite.unwrap_or_default());
+                    totals
+                }
+                TestSuitesOrTestSuite::TestSuite(mut item) => {
+                    recount_inconsistent(&mut item);
+                    let totals = Totals::of_testsuite(&item);
+                    merged.timestamp = earliest(merged.timestamp, item.timestamp.clone());
+                    testsuite.push(*item);
+                    totals
+                }
+            };
+            merged.tests = add(merged.tests, totals.tests);
+            merged.failures = add(merged.failures, totals.failures);
+            merged.errors = add(merged.errors, totals.errors);
+            merged.skipped = add(merged.skipped, totals.skipped);
+            merged.assertions = add(merged.assertions, totals.assertions);
+            merged.time = add_time(merged.time, totals.time);
+        }
+        if options.merge_same_name {
+            testsuite = merge_same_name(testsuite);
+        }
+        merged.testsuite = (!testsuite.is_empty()).then_some(testsuite);
+        merged
+    }
+}
+
+/// Counters of a report.
+#[derive(Default)]
+struct Totals {
+    tests: Option<u32>,
+    failures: Option<u32>,
+    errors: Option<u32>,
+    skipped: Option<u32>,
+    assertions: Option<u32>,
+    time: Option<f32>,
+}
+impl Totals {
+    /// Counters of `<testsuites>`, or the sums of its `testsuite` when it does not have them.
+    fn of_testsuites(testsuites: &TestSuites) -> Self {
+        let mut sums = Totals::default();
+        for testsuite in testsuites.testsuite.iter().flatten() {
+            let totals = Totals::of_testsuite(testsuite);
+            sums.tests = add(sums.tests, totals.tests);
+            sums.failures = add(sums.failures, totals.failures);
+            sums.errors = add(sums.errors, totals.errors);
+            sums.skipped = add(sums.skipped, totals.skipped);
+            sums.time = add_time(sums.time, totals.time);
+        }
+        Totals {
+            tests: testsuites.tests.or(sums.tests),
+            failures: testsuites.failures.or(sums.failures),
+            errors: testsuites.errors.or(sums.errors),
+            skipped: testsuites.skipped.or(sums.skipped),
+            assertions: testsuites.assertions,
+            time: testsuites.time.or(sums.time),
+        }
+    }
+    fn of_testsuite(testsuite: &TestSuite) -> Self {
+        Totals {
+            tests: testsuite.tests,
+            failures: testsuite.failures,
+            errors: testsuite.errors,
+            skipped: testsuite.skipped,
+            assertions: None,
+            time: testsuite.time,
+        }
+    }
+}
+
+/// Merge `<testsuite>` elements with the same name into the first one, keeping the order of the first appearance.
+fn merge_same_name(testsuites: Vec<TestSuite>) -> Vec<TestSuite> {
+    let mut merged: Vec<TestSuite> = vec![];
+    for testsuite in testsuites {
+        let same = testsuite.name.as_ref().and_then(|name| {
+            merged
+                .iter()
+                .position(|item| item.name.as_ref() == Some(name))
+        });
+        match same {
+            Some(index) => merge_testsuite(&mut merged[index], testsuite),
+            None => merged.push(testsuite),
+        }
+    }
+    for testsuite in merged.iter_mut() {
+        testsuite.testsuite = testsuite.testsuite.take().map(merge_same_name);
+    }
+    merged
+}
+
+/// Merge `other` into `testsuite`. Attributes that are not counters are kept from `testsuite` if it has them.
+fn merge_testsuite(testsuite: &mut TestSuite, other: TestSuite) {
+    testsuite.tests = add(testsuite.tests, other.tests);
+    testsuite.failures = add(testsuite.failures, other.failures);
+    testsuite.errors = add(testsuite.errors, other.errors);
+    testsuite.disabled = add(testsuite.disabled, other.disabled);
+    testsuite.skipped = add(testsuite.skipped, other.skipped);
+    testsuite.time = add_time(testsuite.time, other.time);
+    testsuite.timestamp = earliest(testsuite.timestamp.take(), other.timestamp);
+    let keep = |field: &mut Option<String>, other: Option<String>| {
+        if field.is_none() {
+            *field = other;
+        }
+    };
+    keep(&mut testsuite.group, other.group);
+    keep(&mut testsuite.hostname, other.hostname);
+    keep(&mut testsuite.id, other.id);
+    keep(&mut testsuite.package, other.package);
+    keep(&mut testsuite.file, other.file);
+    keep(&mut testsuite.log, other.log);
+    keep(&mut testsuite.url, other.url);
+
+    testsuite.system_out = concat(testsuite.system_out.take(), other.system_out);
+    testsuite.system_err = concat(testsuite.system_err.take(), other.system_err);
+    testsuite.testcase = concat(testsuite.testcase.take(), other.testcase);
+    testsuite.testsuite = concat(testsuite.testsuite.take(), other.testsuite);
+    testsuite.properties = match (testsuite.properties.take(), other.properties) {
+        (Some(properties), Some(other)) => {
+            let mut property = properties.property.unwrap_or_default();
+            for item in other.property.unwrap_or_default() {
+                if !property.contains(&item) {
+                    property.push(item);
+                }
+            }
+            Some(Properties {
+                property: Some(property),
+            })
+        }
+        (properties, other) => properties.or(other),
+    };
+    testsuite.extra = merge_extra(testsuite.extra.take(), other.extra);
+}
+
+fn add<T: Add<Output = T>>(a: Option<T>, b: Option<T>) -> Option<T> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a + b),
+        (a, b) => a.or(b),
+    }
+}
+
+/// Add `time` through its decimal string, so that the sum of `0.1` and `0.2` is `0.3`
+/// instead of accumulating the error of f32.
+pub(crate) fn add_time(a: Option<f32>, b: Option<f32>) -> Option<f32> {
+    let decimal = |time: f32| time.to_string().parse::<f64>().unwrap_or(time as f64);
+    add(a.map(decimal), b.map(decimal)).map(|time| time as f32)
+}
+
+/// The earliest of ISO 8601 timestamps.
+fn earliest(a: Option<String>, b: Option<String>) -> Option<String> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+fn concat<T>(a: Option<Vec<T>>, b: Option<Vec<T>>) -> Option<Vec<T>> {
+    match (a, b) {
+        (Some(mut a), Some(b)) => {
+            a.extend(b);
+            Some(a)
+        }
+        (a, b) => a.or(b),
+    }
+}
+
+type Extra = serde_json::Map<String, serde_json::Value>;
+
+/// Merge `extra`. Attributes are kept from `a`, and child elements are concatenated.
+fn merge_extra(a: Option<Extra>, b: Option<Extra>) -> Option<Extra> {
+    match (a, b) {
+        (Some(mut a), Some(b)) => {
+            for (key, value) in b {
+                match (a.get_mut(&key), value) {
+                    (Some(serde_json::Value::Array(items)), serde_json::Value::Array(other)) => {
+                        items.extend(other)
+                    }
+                    (Some(_), _) => {}
+                    (None, value) => {
+                        a.insert(key, value);
+                    }
+                }
+            }
+            Some(a)
+        }
+        (a, b) => a.or(b),
+    }
+}
@@ -0,0 +1,161 @@
+//! Metadata of the CI run like the commit, branch, pipeline and job that produced the report,
+//! detected from well-known environment variables of CI services.
+
+use serde_json::{Map, Value};
+
+/// Detect metadata of the CI run from environment variables of GitHub Actions, GitLab CI, CircleCI, Jenkins and Buildkite.
+///
+/// `env` returns the value of an environment variable, like `|name| std::env::var(name).ok()`.
+/// The keys are the same for every CI: `ci` that is the name of the CI like `github-actions`, `repository`, `commit`,
+/// `branch`, `tag`, `pull_request`, `workflow`, `pipeline`, `pipeline_url`, `job` and `job_url`.
+/// Keys whose variables are unset or empty are omitted, and it is empty outside of the known CIs.
+///
+/// # Examples
+/// ```
+/// use junit2json;
+/// use std::collections::HashMap;
+///
+/// let env = HashMap::from([
+///     ("GITLAB_CI", "true"),
+///     ("CI_COMMIT_SHA", "0123abc"),
+///     ("CI_COMMIT_BRANCH", "main"),
+///     ("CI_PIPELINE_ID", "42"),
+/// ]);
+/// let metadata = junit2json::detect_ci_metadata(|name| env.get(name).map(|value| value.to_string()));
+/// assert_eq!(
+///     serde_json::Value::Object(metadata),
+///     serde_json::json!({"ci": "gitlab-ci", "commit": "0123abc", "branch": "main", "pipeline": "42"})
+/// );
+/// ```
+pub fn detect_ci_metadata(env: impl Fn(&str) -> Option<String>) -> Map<String, Value> {
+    let env = |name: &str| env(name).filter(|value| !value.is_empty());
+    let is_set = |name: &str| env(name).is_some();
+    let ci: [(&str, Option<String>); 11] = if is_set("GITHUB_ACTIONS") {
+        let run_url = match (
+            env("GITHUB_SERVER_URL"),
+            env("GITHUB_REPOSITORY"),
+            env("GITHUB_RUN_ID"),
+        ) {
+            (Some(server), Some(repository), Some(run)) => {
+                Some(format!("{}/{}/actions/runs/{}", server, repository, run))
+            }
+            _ => None,
+        };
+        let is_tag = env("GITHUB_REF_TYPE").as_deref() == Some("tag");
+        [
+            ("ci", Some("github-actions".to_string())),
+            ("repository", env("GITHUB_REPOSITORY")),
+            ("commit", env("GITHUB_SHA")),
+            // GITHUB_REF_NAME of pull requests is like `123/merge`
+            (
+                "branch",
+                env("GITHUB_HEAD_REF").or(env("GITHUB_REF_NAME").filter(|_| {
+                    env("GITHUB_REF").is_some_and(|r#ref| r#ref.starts_with("refs/heads/"))
+                })),
+            ),
+            ("tag", env("GITHUB_REF_NAME").filter(|_| is_tag)),
+            (
+                "pull_request",
+                env("GITHUB_REF").and_then(|r#ref| {
+                    let number = r#ref.strip_prefix("refs/pull/")?.split('/').next()?;
+                    Some(number.to_string())
+                }),
+            ),
+            ("workflow", env("GITHUB_WORKFLOW")),
+            ("pipeline", env("GITHUB_RUN_ID")),
+            ("pipeline_url", run_url),
+            ("job", env("GITHUB_JOB")),
+            ("job_url", None),
+        ]
+    } else if is_set("GITLAB_CI") {
+        [
+            ("ci", Some("gitlab-ci".to_string())),
+            ("repository", env("CI_PROJECT_PATH")),
+            ("commit", env("CI_COMMIT_SHA")),
+            (
+                "branch",
+                env("CI_COMMIT_BRANCH").or(env("CI_MERGE_REQUEST_SOURCE_BRANCH_NAME")),
+            ),
+            ("tag", env("CI_COMMIT_TAG")),
+            ("pull_request", env("CI_MERGE_REQUEST_IID")),
+            ("workflow", None),
+            ("pipeline", env("CI_PIPELINE_ID")),
+            ("pipeline_url", env("CI_PIPELINE_URL")),
+            ("job", env("CI_JOB_NAME")),
+            ("job_url", env("CI_JOB_URL")),
+        ]
+    } else if is_set("CIRCLECI") {
+        let repository = match (
+            env("CIRCLE_PROJECT_USERNAME"),
+            env("CIRCLE_PROJECT_REPONAME"),
+        ) {
+            (Some(owner), Some(name)) => Some(format!("{}/{}", owner, name)),
+            _ => None,
+        };
+        // CIRCLE_PULL_REQUEST is the URL like `https://github.com/owner/repo/pull/123`
+        let pull_request = env("CIRCLE_PR_NUMBER").or(env("CIRCLE_PULL_REQUEST")
+            .and_then(|url| url.rsplit('/').next().map(|number| number.to_string())));
+        [
+            ("ci", Some("circleci".to_string())),
+            ("repository", repository),
+            ("commit", env("CIRCLE_SHA1")),
+            ("branch", env("CIRCLE_BRANCH")),
+            ("tag", env("CIRCLE_TAG")),
+            ("pull_request", pull_request),
+            ("workflow", env("CIRCLE_WORKFLOW_ID")),
+            ("pipeline", env("CIRCLE_PIPELINE_ID")),
+            ("pipeline_url", None),
+            ("job", env("CIRCLE_JOB")),
+            ("job_url", env("CIRCLE_BUILD_URL")),
+        ]
+    } else if is_set("JENKINS_URL") {
+        [
+            ("ci", Some("jenkins".to_string())),
+            ("repository", env("GIT_URL")),
+            ("commit", env("GIT_COMMIT")),
+            ("branch", env("BRANCH_NAME").or(env("GIT_BRANCH"))),
+            ("tag", env("TAG_NAME")),
+            ("pull_request", env("CHANGE_ID")),
+            ("workflow", None),
+            ("pipeline", env("BUILD_NUMBER")),
+            ("pipeline_url", env("BUILD_URL")),
+            ("job", env("JOB_NAME")),
+            ("job_url", None),
+        ]
+    } else if is_set("BUILDKITE") {
+        let job_url = match (env("BUILDKITE_BUILD_URL"), env("BUILDKITE_JOB_ID")) {
+            (Some(url), Some(job)) => Some(format!("{}#{}", url, job)),
+            _ => None,
+        };
+        [
+            ("ci", Some("buildkite".to_string())),
+            ("repository", env("BUILDKITE_REPO")),
+            ("commit", env("BUILDKITE_COMMIT")),
+            ("branch", env("BUILDKITE_BRANCH")),
+            ("tag", env("BUILDKITE_TAG")),
+            // BUILDKITE_PULL_REQUEST is `false` for builds that are not of pull requests
+            (
+                "pull_request",
+                env("BUILDKITE_PULL_REQUEST").filter(|number| number != "false"),
+            ),
+            ("workflow", env("BUILDKITE_PIPELINE_SLUG")),
+            ("pipeline", env("BUILDKITE_BUILD_NUMBER")),
+            ("pipeline_url", env("BUILDKITE_BUILD_URL")),
+            ("job", env("BUILDKITE_LABEL")),
+            ("job_url", job_url),
+        ]
+    } else {
+        return Map::new();
+    };
+    ci.into_iter()
+        .filter_map(|(key, value)| Some((key.to_string(), Value::String(value?))))
+        .collect()
+}
+
+/// Append `metadata` to the JSON object like `{"testsuites": {...}, "metadata": {...}}`.
+/// Nothing is added when the metadata is empty.
+pub fn add_metadata(value: &mut Value, metadata: &Map<String, Value>) {
+    if let (Value::Object(object), false) = (value, metadata.is_empty()) {
+        object.insert("metadata".to_string(), Value::Object(metadata.clone()));
+    }
+}
@@ -0,0 +1,410 @@
+//! Schemas of the JSON that junit2json outputs.
+//!
+//! They are derived from the structs by schemars, so they never drift from the structs.
+//! schemars generates JSON Schema, and it is converted to the other formats like BigQuery schema.
+
+use crate::cli::DetailShape;
+use crate::{FlatTestCase, TestSuitesOrTestSuite};
+use regex::Regex;
+use schemars::generate::SchemaSettings;
+use schemars::JsonSchema;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use serde_with::skip_serializing_none;
+use std::sync::LazyLock;
+
+/// A column of BigQuery table schema.
+///
+/// See <https://cloud.google.com/bigquery/docs/schemas#specifying_a_json_schema_file>
+#[skip_serializing_none]
+#[derive(Serialize, Debug, PartialEq, Clone)]
+pub struct BigQueryField {
+    pub name: String,
+    /// `STRING`, `INTEGER`, `FLOAT`, `BOOLEAN`, `RECORD` or `JSON`
+    pub r#type: String,
+    /// `NULLABLE` or `REPEATED`
+    pub mode: String,
+    pub description: Option<String>,
+    /// Columns of `RECORD`
+    pub fields: Option<Vec<BigQueryField>>,
+}
+
+/// Description of `path` that the CLI adds to each JSON when it has the path of the input file.
+const PATH_DESCRIPTION: &str = "Path of the input file. The CLI adds it with many files, `--output array`, `--output ndjson`, `--stream` or `--flat`";
+
+/// Replace rustdoc links like ``[`TestCase::outcome`]`` in descriptions with the code like `` `TestCase::outcome` ``.
+fn plain_descriptions(schema: &mut Value) {
+    static LINK: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\[(`[^`]+`)\]").unwrap());
+    match schema {
+        Value::Object(object) => {
+            for (key, value) in object.iter_mut() {
+                match (key.as_str(), value) {
+                    ("description", Value::String(description)) => {
+                        *description = LINK.replace_all(description, "$1").into_owned();
+                    }
+                    (_, value) => plain_descriptions(value),
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(plain_descriptions),
+        _ => {}
+    }
+}
+
+/// JSON Schema of the serialized form of `T`.
+fn json_schema_value<T: JsonSchema>() -> Value {
+    let mut schema = SchemaSettings::draft2020_12()
+        .for_serialize()
+        .into_generator()
+        .into_root_schema_for::<T>()
+        .to_value();
+    plain_descriptions(&mut schema);
+    schema
+}
+
+/// Add the optional `path` as the first property of the object schema, or each variant of `oneOf`.
+fn add_path_property(schema: &mut Value) {
+    let add = |object: &mut Value| {
+        if let Some(properties) = object
+            .get_mut("properties")
+            .and_then(|properties| properties.as_object_mut())
+        {
+            let mut tagged = Map::new();
+            tagged.insert(
+                "path".to_string(),
+                serde_json::json!({ "description": PATH_DESCRIPTION, "type": "string" }),
+            );
+            tagged.extend(std::mem::take(properties));
+            *properties = tagged;
+        }
+    };
+    match schema
+        .get_mut("oneOf")
+        .and_then(|variants| variants.as_array_mut())
+    {
+        Some(variants) => variants.iter_mut().for_each(add),
+        None => add(schema),
+    }
+}
+
+/// Make `skipped`, `error` and `failure` an object of the first element like [`crate::TestSuitesOrTestSuite::to_json_value`].
+fn shape_detail_schema(properties: &mut Map<String, Value>) {
+    for property in properties.values_mut() {
+        let is_details = property
+            .pointer("/items/$ref")
+            .is_some_and(|reference| reference == "#/$defs/Detail");
+        if let (true, Some(property)) = (is_details, property.as_object_mut()) {
+            property.remove("type");
+            property.remove("items");
+            property.insert(
+                "anyOf".to_string(),
+                serde_json::json!([{ "$ref": "#/$defs/Detail" }, { "type": "null" }]),
+            );
+        }
+    }
+}
+
+/// The output never has keys that are not fields, because unrecognized items are kept in `extra`.
+fn deny_unknown_properties(schema: &mut Value) {
+    if let Some(schema) = schema.as_object_mut() {
+        if schema.contains_key("properties") && !schema.contains_key("additionalProperties") {
+            schema.insert("additionalProperties".to_string(), Value::Bool(false));
+        }
+    }
+}
+
+fn json_schema_for<T: JsonSchema>(detail_shape: &DetailShape) -> Value {
+    let mut schema = json_schema_value::<T>();
+    deny_unknown_properties(&mut schema);
+    if let Some(defs) = schema
+        .get_mut("$defs")
+        .and_then(|defs| defs.as_object_mut())
+    {
+        defs.values_mut().for_each(deny_unknown_properties);
+    }
+    if let DetailShape::Object = detail_shape {
+        if let Some(properties) = schema
+            .get_mut("properties")
+            .and_then(|properties| properties.as_object_mut())
+        {
+            shape_detail_schema(properties);
+        }
+        if let Some(defs) = schema
+            .get_mut("$defs")
+            .and_then(|defs| defs.as_object_mut())
+        {
+            defs.values_mut()
+                .filter_map(|def| def.get_mut("properties"))
+                .filter_map(|properties| properties.as_object_mut())
+                .for_each(shape_detail_schema);
+        }
+    }
+    add_path_property(&mut schema);
+    schema
+}
+
+/// JSON Schema (draft 2020-12) of the JSON document that the CLI outputs by default.
+///
+/// It has the optional `path` of the input file that the CLI adds with many files or `--output`.
+///
+/// # Examples
+/// ```
+/// use junit2json;
+///
+/// let schema = junit2json::json_schema(&junit2json::cli::DetailShape::Object);
+/// println!("{}", serde_json::to_string_pretty(&schema).unwrap());
+/// ```
+pub fn json_schema(detail_shape: &DetailShape) -> Value {
+    json_schema_for::<TestSuitesOrTestSuite>(detail_shape)
+}
+
+/// JSON Schema (draft 2020-12) of each line of the flattened per-testcase JSON Lines. See [`crate::FlatTestCase`].
+///
+/// It has the optional `path` of the input file that the CLI adds to each line.
+///
+/// # Examples
+/// ```
+/// use junit2json;
+///
+/// let schema = junit2json::json_flat_schema(&junit2json::cli::DetailShape::Object);
+/// println!("{}", serde_json::to_string_pretty(&schema).unwrap());
+/// ```
+pub fn json_flat_schema(detail_shape: &DetailShape) -> Value {
+    json_schema_for::<FlatTestCase>(detail_shape)
+}
+
+/// Returns the type of the schema ignoring `null`, like `"string"` of `["string", "null"]`.
+fn schema_type(schema: &Value) -> Option<&str> {
+    match schema.get("type")? {
+        Value::String(name) => Some(name.as_str()).filter(|name| *name != "null"),
+        Value::Array(names) => names
+            .iter()
+            .filter_map(|name| name.as_str())
+            .find(|name| *name != "null"),
+        _ => None,
+    }
+}
+
+/// Returns the schema without `null`, like `{"$ref": ...}` of `{"anyOf": [{"$ref": ...}, {"type": "null"}]}`.
+fn non_null(schema: &Value) -> &Value {
+    let variants = schema
+        .get("anyOf")
+        .and_then(|variants| variants.as_array())
+        .map(|variants| {
+            variants
+                .iter()
+                .filter(|variant| schema_type(variant).is_some() || variant.get("$ref").is_some())
+                .collect::<Vec<_>>()
+        });
+    match variants.as_deref() {
+        Some([variant]) => variant,
+        _ => schema,
+    }
+}
+
+struct BigQueryConverter<'a> {
+    defs: &'a Map<String, Value>,
+    detail_shape: &'a DetailShape,
+    /// Definitions being converted, to detect recursive structs like nested `testsuite`
+    stack: Vec<&'a str>,
+}
+impl<'a> BigQueryConverter<'a> {
+    fn resolve(&self, schema: &'a Value) -> Option<&'a str> {
+        schema
+            .get("$ref")
+            .and_then(|reference| reference.as_str())
+            .and_then(|reference| reference.strip_prefix("#/$defs/"))
+    }
+
+    /// Columns of an object schema. Variants of `oneOf` like `TestSuitesOrTestSuite` are merged.
+    fn fields(&mut self, schema: &'a Value) -> Vec<BigQueryField> {
+        let mut fields: Vec<BigQueryField> = vec![];
+        if let Some(properties) = schema.get("properties").and_then(|p| p.as_object()) {
+            for (name, property) in properties {
+                fields.push(self.field(name, property));
+            }
+        }
+        for variant in ["oneOf", "anyOf", "allOf"]
+            .iter()
+            .filter_map(|key| schema.get(*key).and_then(|variants| variants.as_array()))
+            .flatten()
+        {
+            for field in self.fields(variant) {
+                if !fields.iter().any(|item| item.name == field.name) {
+                    fields.push(field);
+                }
+            }
+        }
+        fields
+    }
+
+    fn field(&mut self, name: &str, schema: &'a Value) -> BigQueryField {
+        let description = schema
+            .get("description")
+            .and_then(|description| description.as_str())
+            .map(|description| description.to_string());
+        let schema = non_null(schema);
+        let (schema, mode) = match (schema_type(schema), schema.get("items")) {
+            (Some("array"), Some(items)) => {
+                let items = non_null(items);
+                // `skipped`, `error` and `failure` are an object of the first element in `DetailShape::Object`
+                match (self.detail_shape, self.resolve(items)) {
+                    (DetailShape::Object, Some("Detail")) => (items, "NULLABLE"),
+                    _ => (items, "REPEATED"),
+                }
+            }
+            _ => (schema, "NULLABLE"),
+        };
+        let (r#type, fields) = self.column_type(schema);
+        BigQueryField {
+            name: name.to_string(),
+            r#type: r#type.to_string(),
+            mode: mode.to_string(),
+            description,
+            fields,
+        }
+    }
+
+    fn column_type(&mut self, schema: &'a Value) -> (&'static str, Option<Vec<BigQueryField>>) {
+        if let Some(name) = self.resolve(schema) {
+            // BigQuery does not support recursive records, so the nested ones are stored as JSON
+            if self.stack.contains(&name) {
+                return ("JSON", None);
+            }
+            let Some(definition) = self.defs.get(name) else {
+                return ("JSON", None);
+            };
+            self.stack.push(name);
+            let column_type = self.column_type(definition);
+            self.stack.pop();
+            return column_type;
+        }
+        match schema_type(schema) {
+            Some("string") => ("STRING", None),
+            Some("integer") => ("INTEGER", None),
+            Some("number") => ("FLOAT", None),
+            Some("boolean") => ("BOOLEAN", None),
+            Some("object") if schema.get("properties").is_some() => {
+                ("RECORD", Some(self.fields(schema)))
+            }
+            // Objects without fixed keys like `extra`
+            _ => ("JSON", None),
+        }
+    }
+}
+
+fn bigquery_schema_for<T: JsonSchema>(detail_shape: &DetailShape) -> Vec<BigQueryField> {
+    let schema = json_schema_value::<T>();
+    let defs = Map::new();
+    let mut converter = BigQueryConverter {
+        defs: schema
+            .get("$defs")
+            .and_then(|defs| defs.as_object())
+            .unwrap_or(&defs),
+        detail_shape,
+        stack: vec![],
+    };
+    let mut fields = vec![BigQueryField {
+        name: "path".to_string(),
+        r#type: "STRING".to_string(),
+        mode: "NULLABLE".to_string(),
+        description: Some(PATH_DESCRIPTION.to_string()),
+        fields: None,
+    }];
+    fields.extend(converter.fields(&schema));
+    fields
+}
+
+/// BigQuery table schema of the JSON document that the CLI outputs by default.
+///
+/// `testsuite`, `testcase`, `system-out`, `system-err` and `property` are `REPEATED`.
+/// Nested `testsuite` in `testsuite` is `JSON`, because BigQuery does not support recursive records.
+/// The first column is the optional `path` of the input file that the CLI adds with many files or `--output`.
+///
+/// # Examples
+/// ```
+/// use junit2json;
+///
+/// let schema = junit2json::bigquery_schema(&junit2json::cli::DetailShape::Object);
+/// println!("{}", serde_json::to_string_pretty(&schema).unwrap());
+/// ```
+pub fn bigquery_schema(detail_shape: &DetailShape) -> Vec<BigQueryField> {
+    bigquery_schema_for::<TestSuitesOrTestSuite>(detail_shape)
+}
+
+/// BigQuery table schema of the flattened per-testcase JSON Lines. See [`crate::FlatTestCase`].
+///
+/// The first column is the optional `path` of the input file that the CLI adds to each line.
+///
+/// # Examples
+/// ```
+/// use junit2json;
+///
+/// let schema = junit2json::bigquery_flat_schema(&junit2json::cli::DetailShape::Object);
+/// println!("{}", serde_json::to_string_pretty(&schema).unwrap());
+/// ```
+pub fn bigquery_flat_schema(detail_shape: &DetailShape) -> Vec<BigQueryField> {
+    bigquery_schema_for::<FlatTestCase>(detail_shape)
+}
+
+/// Schema of `metadata` that [`crate::add_metadata`] adds.
+fn metadata_schema() -> Value {
+    serde_json::json!({
+        "description": "Metadata of the run like `commit` and `branch` from `--meta` and `--meta-ci`. `to-xml` ignores it",
+        "type": "object",
+        "additionalProperties": { "type": "string" }
+    })
+}
+
+/// Add `metadata` of [`crate::add_metadata`] to the JSON Schema of [`json_schema`] or [`json_flat_schema`].
+///
+/// # Examples
+/// ```
+/// use junit2json;
+///
+/// let schema = junit2json::json_schema(&junit2json::cli::DetailShape::Object);
+/// let schema = junit2json::json_schema_with_metadata(schema);
+/// println!("{}", serde_json::to_string_pretty(&schema).unwrap());
+/// ```
+pub fn json_schema_with_metadata(mut schema: Value) -> Value {
+    let add = |object: &mut Value| {
+        if let Some(properties) = object
+            .get_mut("properties")
+            .and_then(|properties| properties.as_object_mut())
+        {
+            properties.insert("metadata".to_string(), metadata_schema());
+        }
+    };
+    // The JSON document is one of `testsuites` and `testsuite`
+    match schema
+        .get_mut("oneOf")
+        .and_then(|variants| variants.as_array_mut())
+    {
+        Some(variants) => variants.iter_mut().for_each(add),
+        None => add(&mut schema),
+    }
+    schema
+}
+
+/// Add `metadata` of [`crate::add_metadata`] to the BigQuery table schema of [`bigquery_schema`] or [`bigquery_flat_schema`].
+/// It is `JSON`, because its keys are given by `--meta`.
+///
+/// # Examples
+/// ```
+/// use junit2json;
+///
+/// let schema = junit2json::bigquery_flat_schema(&junit2json::cli::DetailShape::Object);
+/// let schema = junit2json::bigquery_schema_with_metadata(schema);
+/// assert_eq!(schema.last().unwrap().name, "metadata");
+/// ```
+pub fn bigquery_schema_with_metadata(mut fields: Vec<BigQueryField>) -> Vec<BigQueryField> {
+    let schema = metadata_schema();
+    fields.push(BigQueryField {
+        name: "metadata".to_string(),
+        r#type: "JSON".to_string(),
+        mode: "NULLABLE".to_string(),
+        description: schema["description"].as_str().map(|item| item.to_string()),
+        fields: None,
+    });
+    fields
+}
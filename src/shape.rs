@@ -0,0 +1,358 @@
+//! Serialize `skipped`, `error` and `failure` of testcases in a [`DetailShape`].
+//!
+//! The structs always serialize them as an array. [`Shaped`] passes the structs through a serializer
+//! that wraps the actual one, and replaces the fields of `TestCase` with their first element in [`DetailShape::Object`].
+
+use crate::cli::DetailShape;
+use serde::ser::{self, Serialize, Serializer};
+
+/// Fields of `TestCase` that are an object of the first element in [`DetailShape::Object`].
+const DETAIL_FIELDS: [&str; 3] = ["skipped", "error", "failure"];
+
+/// `value` serialized with `skipped`, `error` and `failure` of its testcases in `shape`.
+///
+/// A struct with `#[serde(flatten)]` serializes the flattened struct through a serializer of serde,
+/// so the flattened `TestCase` must be wrapped by itself like `ShapedFlatTestCase`.
+pub(crate) struct Shaped<'a, T: ?Sized> {
+    value: &'a T,
+    shape: DetailShape,
+    /// Serialize only the first element of a sequence
+    first: bool,
+}
+impl<'a, T: ?Sized> Shaped<'a, T> {
+    pub(crate) fn new(value: &'a T, shape: DetailShape) -> Self {
+        Shaped {
+            value,
+            shape,
+            first: false,
+        }
+    }
+}
+impl<T: ?Sized + Serialize> Serialize for Shaped<'_, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.value.serialize(ShapeSerializer {
+            inner: serializer,
+            shape: self.shape,
+            first: self.first,
+        })
+    }
+}
+
+struct ShapeSerializer<S> {
+    inner: S,
+    shape: DetailShape,
+    first: bool,
+}
+impl<S: Serializer> Serializer for ShapeSerializer<S> {
+    type Ok = S::Ok;
+    type Error = S::Error;
+    type SerializeSeq = ShapeSeq<S>;
+    type SerializeTuple = Compound<S::SerializeTuple>;
+    type SerializeTupleStruct = Compound<S::SerializeTupleStruct>;
+    type SerializeTupleVariant = Compound<S::SerializeTupleVariant>;
+    type SerializeMap = Compound<S::SerializeMap>;
+    type SerializeStruct = Compound<S::SerializeStruct>;
+    type SerializeStructVariant = Compound<S::SerializeStructVariant>;
+
+    fn serialize_bool(self, v: bool) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_bool(v)
+    }
+    fn serialize_i8(self, v: i8) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_i8(v)
+    }
+    fn serialize_i16(self, v: i16) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_i16(v)
+    }
+    fn serialize_i32(self, v: i32) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_i32(v)
+    }
+    fn serialize_i64(self, v: i64) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_i64(v)
+    }
+    fn serialize_u8(self, v: u8) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_u8(v)
+    }
+    fn serialize_u16(self, v: u16) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_u16(v)
+    }
+    fn serialize_u32(self, v: u32) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_u32(v)
+    }
+    fn serialize_u64(self, v: u64) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_u64(v)
+    }
+    fn serialize_f32(self, v: f32) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_f32(v)
+    }
+    fn serialize_f64(self, v: f64) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_f64(v)
+    }
+    fn serialize_char(self, v: char) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_char(v)
+    }
+    fn serialize_str(self, v: &str) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_str(v)
+    }
+    fn serialize_bytes(self, v: &[u8]) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_bytes(v)
+    }
+    fn serialize_none(self) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_none()
+    }
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_some(&Shaped {
+            value,
+            shape: self.shape,
+            first: self.first,
+        })
+    }
+    fn serialize_unit(self) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_unit()
+    }
+    fn serialize_unit_struct(self, name: &'static str) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_unit_struct(name)
+    }
+    fn serialize_unit_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+    ) -> Result<S::Ok, S::Error> {
+        self.inner
+            .serialize_unit_variant(name, variant_index, variant)
+    }
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<S::Ok, S::Error> {
+        self.inner
+            .serialize_newtype_struct(name, &Shaped::new(value, self.shape))
+    }
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_newtype_variant(
+            name,
+            variant_index,
+            variant,
+            &Shaped::new(value, self.shape),
+        )
+    }
+    fn serialize_seq(self, len: Option<usize>) -> Result<ShapeSeq<S>, S::Error> {
+        match self.first {
+            true => Ok(ShapeSeq::First {
+                inner: Some(self.inner),
+                ok: None,
+                shape: self.shape,
+            }),
+            false => Ok(ShapeSeq::All(Compound::new(
+                self.inner.serialize_seq(len)?,
+                self.shape,
+            ))),
+        }
+    }
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, S::Error> {
+        Ok(Compound::new(self.inner.serialize_tuple(len)?, self.shape))
+    }
+    fn serialize_tuple_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct, S::Error> {
+        Ok(Compound::new(
+            self.inner.serialize_tuple_struct(name, len)?,
+            self.shape,
+        ))
+    }
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant, S::Error> {
+        Ok(Compound::new(
+            self.inner
+                .serialize_tuple_variant(name, variant_index, variant, len)?,
+            self.shape,
+        ))
+    }
+    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, S::Error> {
+        Ok(Compound::new(self.inner.serialize_map(len)?, self.shape))
+    }
+    fn serialize_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStruct, S::Error> {
+        let details = name == "TestCase" && matches!(self.shape, DetailShape::Object);
+        Ok(Compound {
+            details,
+            ..Compound::new(self.inner.serialize_struct(name, len)?, self.shape)
+        })
+    }
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStructVariant, S::Error> {
+        Ok(Compound::new(
+            self.inner
+                .serialize_struct_variant(name, variant_index, variant, len)?,
+            self.shape,
+        ))
+    }
+    fn is_human_readable(&self) -> bool {
+        self.inner.is_human_readable()
+    }
+}
+
+/// Sequence that serializes all elements, or only the first element like an `Option`.
+enum ShapeSeq<S: Serializer> {
+    All(Compound<S::SerializeSeq>),
+    First {
+        inner: Option<S>,
+        ok: Option<S::Ok>,
+        shape: DetailShape,
+    },
+}
+impl<S: Serializer> ser::SerializeSeq for ShapeSeq<S> {
+    type Ok = S::Ok;
+    type Error = S::Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), S::Error> {
+        match self {
+            ShapeSeq::All(seq) => seq.inner.serialize_element(&Shaped::new(value, seq.shape)),
+            ShapeSeq::First { inner, ok, shape } => {
+                if let Some(inner) = inner.take() {
+                    *ok = Some(Shaped::new(value, *shape).serialize(inner)?);
+                }
+                Ok(())
+            }
+        }
+    }
+    fn end(self) -> Result<S::Ok, S::Error> {
+        match self {
+            ShapeSeq::All(seq) => seq.inner.end(),
+            ShapeSeq::First { ok: Some(ok), .. } => Ok(ok),
+            ShapeSeq::First {
+                inner: Some(inner), ..
+            } => inner.serialize_none(),
+            ShapeSeq::First { .. } => unreachable!("the first element is serialized"),
+        }
+    }
+}
+
+/// Compound value whose elements are serialized by [`ShapeSerializer`] too.
+struct Compound<C> {
+    inner: C,
+    shape: DetailShape,
+    /// Whether this is `TestCase` whose `skipped`, `error` and `failure` are serialized as the first element
+    details: bool,
+}
+impl<C> Compound<C> {
+    fn new(inner: C, shape: DetailShape) -> Self {
+        Compound {
+            inner,
+            shape,
+            details: false,
+        }
+    }
+}
+impl<C: ser::SerializeTuple> ser::SerializeTuple for Compound<C> {
+    type Ok = C::Ok;
+    type Error = C::Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), C::Error> {
+        self.inner
+            .serialize_element(&Shaped::new(value, self.shape))
+    }
+    fn end(self) -> Result<C::Ok, C::Error> {
+        self.inner.end()
+    }
+}
+impl<C: ser::SerializeTupleStruct> ser::SerializeTupleStruct for Compound<C> {
+    type Ok = C::Ok;
+    type Error = C::Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), C::Error> {
+        self.inner.serialize_field(&Shaped::new(value, self.shape))
+    }
+    fn end(self) -> Result<C::Ok, C::Error> {
+        self.inner.end()
+    }
+}
+impl<C: ser::SerializeTupleVariant> ser::SerializeTupleVariant for Compound<C> {
+    type Ok = C::Ok;
+    type Error = C::Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), C::Error> {
+        self.inner.serialize_field(&Shaped::new(value, self.shape))
+    }
+    fn end(self) -> Result<C::Ok, C::Error> {
+        self.inner.end()
+    }
+}
+impl<C: ser::SerializeMap> ser::SerializeMap for Compound<C> {
+    type Ok = C::Ok;
+    type Error = C::Error;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), C::Error> {
+        self.inner.serialize_key(key)
+    }
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), C::Error> {
+        self.inner.serialize_value(&Shaped::new(value, self.shape))
+    }
+    fn end(self) -> Result<C::Ok, C::Error> {
+        self.inner.end()
+    }
+}
+impl<C: ser::SerializeStruct> ser::SerializeStruct for Compound<C> {
+    type Ok = C::Ok;
+    type Error = C::Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), C::Error> {
+        let value = Shaped {
+            value,
+            shape: self.shape,
+            first: self.details && DETAIL_FIELDS.contains(&key),
+        };
+        self.inner.serialize_field(key, &value)
+    }
+    fn skip_field(&mut self, key: &'static str) -> Result<(), C::Error> {
+        self.inner.skip_field(key)
+    }
+    fn end(self) -> Result<C::Ok, C::Error> {
+        self.inner.end()
+    }
+}
+impl<C: ser::SerializeStructVariant> ser::SerializeStructVariant for Compound<C> {
+    type Ok = C::Ok;
+    type Error = C::Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), C::Error> {
+        self.inner
+            .serialize_field(key, &Shaped::new(value, self.shape))
+    }
+    fn skip_field(&mut self, key: &'static str) -> Result<(), C::Error> {
+        self.inner.skip_field(key)
+    }
+    fn end(self) -> Result<C::Ok, C::Error> {
+        self.inner.end()
+    }
+}
@@ -0,0 +1,384 @@
+//! Stream testcases one by one without building the whole tree.
+//!
+//! Only the attributes and `<properties>` of the enclosing `<testsuites>` and `<testsuite>` elements are kept
+//! while walking the XML events, so the memory usage is bounded by the size of one `<testcase>`
+//! and one event of the skipped elements like `<system-out>` of a testsuite, whose text is a single event.
+//! Each `<testcase>` element is cut out from the events and deserialized by serde with the same structs as [`crate::from_str`].
+
+use crate::cli::DetailShape;
+use crate::error::{self, ElementPath, Error, Location, PositionReader};
+use crate::shape::Shaped;
+use crate::{
+    FilterTag, Properties, Property, RedactOptions, RedactReport, TestCase, TestSuite, TestSuites,
+};
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::{de, Reader, Writer};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+use std::io::{self, BufRead};
+use std::sync::Arc;
+
+/// Enclosing `<testsuites>` and `<testsuite>` elements of a streamed testcase.
+///
+/// They only have attributes and `properties`, because `testcase`, nested `testsuite`,
+/// `system-out` and `system-err` are not kept while streaming.
+/// `properties` are the ones read so far, so they are missing for testcases before `<properties>`.
+#[skip_serializing_none]
+#[derive(Serialize, JsonSchema, Debug, Default, PartialEq, Clone)]
+pub struct SuiteContext {
+    /// `None` when the root element is `<testsuite>`
+    pub testsuites: Option<TestSuites>,
+    /// Enclosing `<testsuite>` elements from the outermost to the innermost
+    pub testsuite: Vec<TestSuite>,
+}
+impl SuiteContext {
+    /// Returns the innermost `<testsuite>` that directly contains the testcase.
+    pub fn parent(&self) -> Option<&TestSuite> {
+        self.testsuite.last()
+    }
+
+    /// Remove elements and attributes that the tags select from the enclosing elements.
+    /// See [`crate::TestSuitesOrTestSuite::remove_fields`].
+    pub fn remove_fields(&mut self, tags: &[FilterTag]) {
+        if let Some(testsuites) = &mut self.testsuites {
+            testsuites.remove_fields(tags);
+        }
+        self.testsuite
+            .iter_mut()
+            .for_each(|item| item.remove_fields(tags));
+    }
+
+    /// Convert the testcase with this context to one JSON object like
+    /// `{"testsuites": {...}, "testsuite": [{...}], "testcase": {...}}`, that is one line of JSON Lines.
+    ///
+    /// See [`crate::TestSuitesOrTestSuite::to_json_value`] for `shape`.
+    pub fn to_json_value(
+        &self,
+        testcase: &TestCase,
+        shape: &DetailShape,
+    ) -> Result<serde_json::Value, serde_json::Error> {
+        let mut value = crate::to_json_value_exact(self, shape)?;
+        let testcase = crate::to_json_value_exact(testcase, shape)?;
+        if let Some(object) = value.as_object_mut() {
+            object.insert("testcase".to_string(), testcase);
+        }
+        Ok(value)
+    }
+
+    /// Denormalize the testcase with the fields of its testsuite for loading into BigQuery as rows.
+    ///
+    /// `timestamp`, `hostname` and `properties` are taken from the nearest enclosing `<testsuite>` that has them,
+    /// because nested testsuites often omit them.
+    pub fn flatten(&self, testcase: TestCase) -> FlatTestCase {
+        let nearest = |field: fn(&TestSuite) -> Option<&String>| {
+            self.testsuite.iter().rev().find_map(field).cloned()
+        };
+        FlatTestCase {
+            testsuites_name: self
+                .testsuites
+                .as_ref()
+                .and_then(|testsuites| testsuites.name.clone()),
+            testsuite_name: self.parent().and_then(|testsuite| testsuite.name.clone()),
+            testsuite_timestamp: nearest(|testsuite| testsuite.timestamp.as_ref()),
+            testsuite_hostname: nearest(|testsuite| testsuite.hostname.as_ref()),
+            testsuite_properties: self
+                .testsuite
+                .iter()
+                .rev()
+                .find_map(|testsuite| testsuite.properties.as_ref())
+                .and_then(|properties| properties.property.clone()),
+            testcase,
+        }
+    }
+}
+
+/// A testcase denormalized with the fields of its testsuite. See [`SuiteContext::flatten`].
+///
+/// Each of them becomes one line of newline delimited JSON like
+/// `{"testsuites_name": "...", "testsuite_name": "...", "name": "...", "classname": "...", ...}`,
+/// which can be loaded by `bq load --source_format=NEWLINE_DELIMITED_JSON`.
+#[skip_serializing_none]
+#[derive(Serialize, JsonSchema, Debug, Default, PartialEq, Clone)]
+pub struct FlatTestCase {
+    /// `name` of `<testsuites>`
+    pub testsuites_name: Option<String>,
+    /// `name` of the innermost `<testsuite>`
+    pub testsuite_name: Option<String>,
+    pub testsuite_timestamp: Option<String>,
+    pub testsuite_hostname: Option<String>,
+    pub testsuite_properties: Option<Vec<Property>>,
+    #[serde(flatten)]
+    pub testcase: TestCase,
+}
+impl FlatTestCase {
+    /// Convert to `serde_json::Value`.
+    /// See [`crate::TestSuitesOrTestSuite::to_json_value`] for `shape`.
+    pub fn to_json_value(
+        &self,
+        shape: &DetailShape,
+    ) -> Result<serde_json::Value, serde_json::Error> {
+        let shaped = ShapedFlatTestCase {
+            testsuites_name: self.testsuites_name.as_ref(),
+            testsuite_name: self.testsuite_name.as_ref(),
+            testsuite_timestamp: self.testsuite_timestamp.as_ref(),
+            testsuite_hostname: self.testsuite_hostname.as_ref(),
+            testsuite_properties: self.testsuite_properties.as_ref(),
+            testcase: Shaped::new(&self.testcase, *shape),
+        };
+        crate::to_json_value_exact(&shaped, shape)
+    }
+}
+
+/// [`FlatTestCase`] whose flattened testcase is shaped by itself, because serde serializes it with its own serializer.
+#[skip_serializing_none]
+#[derive(Serialize)]
+struct ShapedFlatTestCase<'a> {
+    testsuites_name: Option<&'a String>,
+    testsuite_name: Option<&'a String>,
+    testsuite_timestamp: Option<&'a String>,
+    testsuite_hostname: Option<&'a String>,
+    testsuite_properties: Option<&'a Vec<Property>>,
+    #[serde(flatten)]
+    testcase: Shaped<'a, TestCase>,
+}
+
+/// Iterator over the testcases of JUnit XML. See [`crate::stream_from_reader`].
+///
+/// It stops after the first error.
+/// The line and column of [`Error::Syntax`] can point to where the reader stopped,
+/// which is a little after the actual problem.
+pub struct TestCaseStream<R> {
+    reader: Reader<PositionReader<R>>,
+    buf: Vec<u8>,
+    fragment_buf: Vec<u8>,
+    path: ElementPath,
+    context: Arc<SuiteContext>,
+    redact_options: Option<RedactOptions>,
+    redact_report: RedactReport,
+    done: bool,
+}
+
+impl<R: BufRead> TestCaseStream<R> {
+    pub fn new(reader: R) -> Self {
+        TestCaseStream {
+            reader: Reader::from_reader(PositionReader::new(reader)),
+            buf: vec![],
+            fragment_buf: vec![],
+            path: ElementPath::default(),
+            context: Arc::new(SuiteContext::default()),
+            redact_options: None,
+            redact_report: RedactReport::default(),
+            done: false,
+        }
+    }
+
+    /// Redact secrets in the enclosing elements when they are read, so each one is redacted and counted once
+    /// however many testcases share it. Testcases are not redacted, see [`crate::TestCase::redact`].
+    pub fn redact(mut self, options: RedactOptions) -> Self {
+        self.redact_options = Some(options);
+        self
+    }
+
+    /// Numbers of redactions in the enclosing elements read so far. See [`TestCaseStream::redact`].
+    pub fn redact_report(&self) -> &RedactReport {
+        &self.redact_report
+    }
+
+    fn location(&self, offset: u64) -> Location {
+        self.reader
+            .get_ref()
+            .location(offset, self.path.to_string())
+    }
+
+    fn xml_error(&self, err: quick_xml::Error) -> Error {
+        match err {
+            quick_xml::Error::Io(err) => Error::Io(io::Error::new(err.kind(), err.to_string())),
+            err => Error::Syntax {
+                message: err.to_string(),
+                location: self.location(self.reader.error_position()),
+            },
+        }
+    }
+
+    fn unexpected_eof(&self) -> Error {
+        Error::Syntax {
+            message: "unexpected end of XML".to_string(),
+            location: self.location(self.reader.buffer_position()),
+        }
+    }
+
+    /// Read events until the end of the element and returns the element as an XML fragment.
+    fn read_fragment(&mut self, start: BytesStart, empty: bool) -> Result<String, Error> {
+        let mut writer = Writer::new(Vec::new());
+        if empty {
+            writer.write_event(Event::Empty(start))?;
+        } else {
+            writer.write_event(Event::Start(start))?;
+            let mut depth = 1;
+            while depth > 0 {
+                self.fragment_buf.clear();
+                let event = match self.reader.read_event_into(&mut self.fragment_buf) {
+                    Ok(event) => event,
+                    Err(err) => return Err(self.xml_error(err)),
+                };
+                match event {
+                    Event::Start(_) => depth += 1,
+                    Event::End(_) => depth -= 1,
+                    Event::Eof => return Err(self.unexpected_eof()),
+                    _ => {}
+                }
+                writer.write_event(event)?;
+            }
+        }
+        String::from_utf8(writer.into_inner())
+            .map_err(|err| Error::Io(io::Error::new(io::ErrorKind::InvalidData, err)))
+    }
+
+    /// Deserialize an XML fragment that starts at `start`.
+    fn deserialize_fragment<T: for<'de> Deserialize<'de>>(
+        fragment: &str,
+        parents: ElementPath,
+        start: &Location,
+    ) -> Result<T, Error> {
+        let mut deserializer = de::Deserializer::from_str(fragment);
+        T::deserialize(&mut deserializer).map_err(|err| {
+            let position = deserializer.get_ref().get_ref().buffer_position();
+            let mut err = error::locate_in(fragment, err, position, parents);
+            if let Some(location) = err.location_mut() {
+                location.shift(start);
+            }
+            err
+        })
+    }
+
+    /// Returns the attributes of the start tag as `T`.
+    fn deserialize_start<T: for<'de> Deserialize<'de>>(
+        &mut self,
+        start: BytesStart,
+        parents: ElementPath,
+        location: &Location,
+    ) -> Result<T, Error> {
+        let fragment = self.read_fragment(start, true)?;
+        Self::deserialize_fragment(&fragment, parents, location)
+    }
+
+    fn next_testcase(&mut self) -> Result<Option<(Arc<SuiteContext>, TestCase)>, Error> {
+        loop {
+            let offset = self.reader.buffer_position();
+            let mut start_location = self.location(offset);
+            self.buf.clear();
+            let (start, empty) = match self.reader.read_event_into(&mut self.buf) {
+                Ok(Event::Start(start)) => (start.into_owned(), false),
+                Ok(Event::Empty(start)) => (start.into_owned(), true),
+                Ok(Event::End(end)) => {
+                    if end.name().as_ref() == b"testsuite" {
+                        Arc::make_mut(&mut self.context).testsuite.pop();
+                    }
+                    self.path.pop();
+                    if self.path.is_empty() {
+                        return Ok(None);
+                    }
+                    continue;
+                }
+                Ok(Event::Eof) => match self.path.is_empty() {
+                    true => return Ok(None),
+                    false => return Err(self.unexpected_eof()),
+                },
+                Ok(_) => continue,
+                Err(err) => return Err(self.xml_error(err)),
+            };
+
+            let name = String::from_utf8_lossy(start.name().as_ref()).into_owned();
+            let parents = self.path.clone();
+            self.path.push(&name);
+            start_location.path = self.path.to_string();
+            match (parents.is_empty(), self.path.parent_name(), name.as_str()) {
+                (true, _, "testsuites") => {
+                    let mut testsuites: TestSuites =
+                        self.deserialize_start(start, parents, &start_location)?;
+                    if let Some(options) = &self.redact_options {
+                        self.redact_report.merge(testsuites.redact(options));
+                    }
+                    Arc::make_mut(&mut self.context).testsuites = Some(testsuites);
+                }
+                (true, _, "testsuite") | (false, Some("testsuites" | "testsuite"), "testsuite") => {
+                    let mut testsuite: TestSuite =
+                        self.deserialize_start(start, parents, &start_location)?;
+                    if let Some(options) = &self.redact_options {
+                        self.redact_report.merge(testsuite.redact(options));
+                    }
+                    Arc::make_mut(&mut self.context).testsuite.push(testsuite);
+                }
+                (true, _, _) => {
+                    return Err(Error::UnexpectedRoot {
+                        name,
+                        location: start_location,
+                    })
+                }
+                (false, Some("testsuite"), "testcase") => {
+                    let fragment = self.read_fragment(start, empty)?;
+                    let mut testcase: TestCase =
+                        Self::deserialize_fragment(&fragment, parents, &start_location)?;
+                    testcase.trim_empty_items();
+                    self.path.pop();
+                    return Ok(Some((Arc::clone(&self.context), testcase)));
+                }
+                (false, Some("testsuite"), "properties") => {
+                    let fragment = self.read_fragment(start, empty)?;
+                    let mut properties: Properties =
+                        Self::deserialize_fragment(&fragment, parents, &start_location)?;
+                    properties.trim_empty_items();
+                    let mut properties = properties.property.is_some().then_some(properties);
+                    if let Some(options) = &self.redact_options {
+                        options.properties(&mut properties, &mut self.redact_report);
+                    }
+                    if let Some(testsuite) = Arc::make_mut(&mut self.context).testsuite.last_mut() {
+                        testsuite.properties = properties;
+                    }
+                    self.path.pop();
+                    continue;
+                }
+                _ => {
+                    // Skip `<system-out>`, `<system-err>` and unknown elements without keeping them.
+                    // `read_to_end_into` clears the buffer after each event, so only one event is held.
+                    if !empty {
+                        self.fragment_buf.clear();
+                        if let Err(err) = self
+                            .reader
+                            .read_to_end_into(start.name(), &mut self.fragment_buf)
+                        {
+                            return Err(self.xml_error(err));
+                        }
+                    }
+                    self.path.pop();
+                    continue;
+                }
+            }
+            if empty {
+                if name == "testsuite" {
+                    Arc::make_mut(&mut self.context).testsuite.pop();
+                }
+                self.path.pop();
+                if self.path.is_empty() {
+                    return Ok(None);
+                }
+            }
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for TestCaseStream<R> {
+    type Item = Result<(Arc<SuiteContext>, TestCase), Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let next = self.next_testcase().transpose();
+        if !matches!(next, Some(Ok(_))) {
+            self.done = true;
+        }
+        next
+    }
+}
@@ -0,0 +1,204 @@
+//! Strip ANSI escape sequences like colors of test runners from texts like `system-out`.
+//!
+//! XML 1.0 does not allow ESC, so some writers drop it and leave `[31m` in the text.
+//! Such SGR sequences without ESC are stripped only with [`StripMode::OrphanSgr`],
+//! because they can't be told apart from ordinary text like `retry in [5m]` for sure.
+
+use crate::cli::StripMode;
+use crate::{Detail, Rerun, TestCase, TestSuite, TestSuitesOrTestSuite};
+
+const ESC: u8 = 0x1b;
+const BEL: u8 = 0x07;
+
+/// End of the escape sequence after ESC at `start`.
+fn escape_end(bytes: &[u8], start: usize) -> usize {
+    match bytes.get(start) {
+        Some(b'[') => csi_end(bytes, start + 1),
+        // OSC, DCS, SOS, PM and APC end with BEL or ST (ESC \)
+        Some(b']' | b'P' | b'X' | b'^' | b'_') => {
+            let mut end = start + 1;
+            while end < bytes.len() {
+                match bytes[end] {
+                    BEL => return end + 1,
+                    ESC if bytes.get(end + 1) == Some(&b'\\') => return end + 2,
+                    _ => end += 1,
+                }
+            }
+            end
+        }
+        Some(0x20..=0x2f) => {
+            let mut end = start + 1;
+            while matches!(bytes.get(end), Some(0x20..=0x2f)) {
+                end += 1;
+            }
+            match bytes.get(end) {
+                Some(0x30..=0x7e) => end + 1,
+                _ => end,
+            }
+        }
+        Some(0x30..=0x7e) => start + 1,
+        _ => start,
+    }
+}
+
+/// End of the CSI sequence whose parameters start at `start`.
+fn csi_end(bytes: &[u8], start: usize) -> usize {
+    let mut end = start;
+    while matches!(bytes.get(end), Some(0x20..=0x3f)) {
+        end += 1;
+    }
+    match bytes.get(end) {
+        Some(0x40..=0x7e) => end + 1,
+        _ => end,
+    }
+}
+
+/// End of the SGR sequence without ESC like `[1;31m` whose parameters start at `start`.
+///
+/// Each parameter has 1 to 3 digits, and it is not followed by `]` to keep texts like `list[1m]`.
+fn orphan_sgr_end(bytes: &[u8], start: usize) -> Option<usize> {
+    let mut end = start;
+    loop {
+        let digits = bytes[end..]
+            .iter()
+            .take_while(|byte| byte.is_ascii_digit())
+            .count();
+        if !(1..=3).contains(&digits) {
+            return None;
+        }
+        end += digits;
+        match bytes.get(end) {
+            Some(b';') => end += 1,
+            Some(b'm') if bytes.get(end + 1) != Some(&b']') => return Some(end + 1),
+            _ => return None,
+        }
+    }
+}
+
+/// Strip ANSI escape sequences from the text, and SGR sequences without ESC too when `orphan` is true.
+fn strip_ansi(text: &str, orphan: bool) -> String {
+    let bytes = text.as_bytes();
+    let mut stripped = String::with_capacity(text.len());
+    let mut kept = 0;
+    let mut index = 0;
+    while index < bytes.len() {
+        let end = match bytes[index] {
+            ESC => Some(escape_end(bytes, index + 1)),
+            // C1 CSI (U+009B) in UTF-8
+            0xc2 if bytes.get(index + 1) == Some(&0x9b) => Some(csi_end(bytes, index + 2)),
+            b'[' if orphan => orphan_sgr_end(bytes, index + 1),
+            _ => None,
+        };
+        match end {
+            Some(end) => {
+                stripped.push_str(&text[kept..index]);
+                kept = end;
+                index = end;
+            }
+            None => index += 1,
+        }
+    }
+    stripped.push_str(&text[kept..]);
+    stripped
+}
+
+fn strip(text: &mut String, mode: &StripMode) {
+    let stripped = strip_ansi(text, matches!(mode, StripMode::OrphanSgr));
+    *text = match mode {
+        StripMode::Ansi | StripMode::OrphanSgr => stripped,
+        StripMode::Control => stripped
+            .chars()
+            .filter(|c| !c.is_control() || matches!(c, '\t' | '\n' | '\r'))
+            .collect(),
+    };
+}
+
+fn strip_option(text: &mut Option<String>, mode: &StripMode) {
+    if let Some(text) = text {
+        strip(text, mode);
+    }
+}
+
+fn strip_outputs(texts: &mut Option<Vec<String>>, mode: &StripMode) {
+    texts
+        .iter_mut()
+        .flatten()
+        .for_each(|text| strip(text, mode));
+}
+
+impl TestSuitesOrTestSuite {
+    /// Strip ANSI escape sequences, or control characters too, from `system-out`, `system-err`
+    /// and texts of `skipped`, `error`, `failure` and reruns. See [`StripMode`].
+    ///
+    /// # Examples
+    /// ```
+    /// use junit2json;
+    ///
+    /// let xml = r#"
+    ///   <testsuite name="suite1">
+    ///       <testcase name="case1">
+    ///           <failure message="assertion failed">[1mDiff[0m [31m&lt; left[0m</failure>
+    ///       </testcase>
+    ///   </testsuite>
+    /// "#;
+    /// let mut testsuite = junit2json::from_str(xml).unwrap();
+    /// testsuite.strip_texts(&junit2json::cli::StripMode::OrphanSgr);
+    /// let json = testsuite.to_json_value(&junit2json::cli::DetailShape::Object).unwrap();
+    /// assert_eq!(json["testsuite"]["testcase"][0]["failure"]["inner"], "Diff < left");
+    /// ```
+    pub fn strip_texts(&mut self, mode: &StripMode) {
+        match self {
+            TestSuitesOrTestSuite::TestSuites(testsuites) => testsuites
+                .testsuite
+                .iter_mut()
+                .flatten()
+                .for_each(|item| item.strip_texts(mode)),
+            TestSuitesOrTestSuite::TestSuite(testsuite) => testsuite.strip_texts(mode),
+        }
+    }
+}
+
+impl TestSuite {
+    /// Strip escape sequences from texts. See [`TestSuitesOrTestSuite::strip_texts`].
+    pub fn strip_texts(&mut self, mode: &StripMode) {
+        strip_outputs(&mut self.system_out, mode);
+        strip_outputs(&mut self.system_err, mode);
+        if let Some(testcase) = &mut self.testcase {
+            testcase.iter_mut().for_each(|item| item.strip_texts(mode));
+        }
+        if let Some(testsuite) = &mut self.testsuite {
+            testsuite.iter_mut().for_each(|item| item.strip_texts(mode));
+        }
+    }
+}
+
+impl TestCase {
+    /// Strip escape sequences from texts. See [`TestSuitesOrTestSuite::strip_texts`].
+    pub fn strip_texts(&mut self, mode: &StripMode) {
+        strip_outputs(&mut self.system_out, mode);
+        strip_outputs(&mut self.system_err, mode);
+        [&mut self.skipped, &mut self.error, &mut self.failure]
+            .into_iter()
+            .flatten()
+            .flatten()
+            .for_each(|detail: &mut Detail| {
+                strip_option(&mut detail.message, mode);
+                strip_option(&mut detail.inner, mode);
+            });
+        [
+            &mut self.flaky_failure,
+            &mut self.flaky_error,
+            &mut self.rerun_failure,
+            &mut self.rerun_error,
+        ]
+        .into_iter()
+        .flatten()
+        .flatten()
+        .for_each(|rerun: &mut Rerun| {
+            strip_option(&mut rerun.message, mode);
+            strip_option(&mut rerun.stack_trace, mode);
+            strip_outputs(&mut rerun.system_out, mode);
+            strip_outputs(&mut rerun.system_err, mode);
+        });
+    }
+}
@@ -0,0 +1,163 @@
+//! Truncate oversized texts like `system-out` that exceed the row size limit of BigQuery.
+
+use crate::cli::TruncateKeep;
+use crate::{Detail, Rerun, TestCase, TestSuite, TestSuitesOrTestSuite};
+
+/// Options for [`TestSuitesOrTestSuite::truncate_texts`].
+#[derive(Debug, Default, Clone)]
+pub struct TruncateOptions {
+    /// Max bytes of each `system-out` and `system-err`
+    pub max_output_bytes: Option<usize>,
+    /// Max bytes of `message` and `inner` of `skipped`, `error` and `failure`, and `message` and `stackTrace` of reruns
+    pub max_detail_bytes: Option<usize>,
+    /// Which part of a truncated text is kept
+    pub keep: TruncateKeep,
+}
+
+/// Marker put at the cut point of a truncated text.
+fn marker(original: usize) -> String {
+    format!("[... truncated from {} bytes ...]", original)
+}
+
+/// Truncate the text to `max` bytes including the marker, cutting at UTF-8 character boundaries.
+///
+/// The result is longer than `max` only when the marker itself is longer than `max`.
+fn truncate(text: &mut String, max: usize, keep: &TruncateKeep) {
+    if text.len() <= max {
+        return;
+    }
+    let marker = marker(text.len());
+    let budget = max.saturating_sub(marker.len());
+    let head_end = |len: usize| {
+        let mut end = len;
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        end
+    };
+    let tail_start = |len: usize| {
+        let mut start = text.len() - len;
+        while !text.is_char_boundary(start) {
+            start += 1;
+        }
+        start
+    };
+    let truncated = match keep {
+        TruncateKeep::Head => format!("{}{}", &text[..head_end(budget)], marker),
+        TruncateKeep::Tail => format!("{}{}", marker, &text[tail_start(budget)..]),
+        TruncateKeep::Both => format!(
+            "{}{}{}",
+            &text[..head_end(budget - budget / 2)],
+            marker,
+            &text[tail_start(budget / 2)..]
+        ),
+    };
+    *text = truncated;
+}
+
+impl TruncateOptions {
+    fn output(&self, text: &mut String) {
+        if let Some(max) = self.max_output_bytes {
+            truncate(text, max, &self.keep);
+        }
+    }
+    fn outputs(&self, texts: &mut Option<Vec<String>>) {
+        texts
+            .iter_mut()
+            .flatten()
+            .for_each(|text| self.output(text));
+    }
+    fn detail(&self, text: &mut Option<String>) {
+        if let (Some(max), Some(text)) = (self.max_detail_bytes, text) {
+            truncate(text, max, &self.keep);
+        }
+    }
+}
+
+impl TestSuitesOrTestSuite {
+    /// Truncate `system-out`, `system-err` and texts of `skipped`, `error`, `failure` and reruns that exceed the limits.
+    ///
+    /// Each text is cut at a UTF-8 character boundary, and a marker like `[... truncated from 10485760 bytes ...]`
+    /// is put at the cut point. The truncated text including the marker fits in the limit.
+    ///
+    /// # Examples
+    /// ```
+    /// use junit2json;
+    ///
+    /// let xml = r#"
+    ///   <testsuite name="suite1">
+    ///       <testcase name="case1">
+    ///           <system-out>very long output that is too long to load</system-out>
+    ///       </testcase>
+    ///   </testsuite>
+    /// "#;
+    /// let mut testsuite = junit2json::from_str(xml).unwrap();
+    /// let options = junit2json::TruncateOptions {
+    ///     max_output_bytes: Some(40),
+    ///     keep: junit2json::cli::TruncateKeep::Tail,
+    ///     ..Default::default()
+    /// };
+    /// testsuite.truncate_texts(&options);
+    /// let json = testsuite.to_json_value(&junit2json::cli::DetailShape::Object).unwrap();
+    /// assert_eq!(json["testsuite"]["testcase"][0]["system-out"][0], "[... truncated from 41 bytes ...]to load");
+    /// ```
+    pub fn truncate_texts(&mut self, options: &TruncateOptions) {
+        match self {
+            TestSuitesOrTestSuite::TestSuites(testsuites) => testsuites
+                .testsuite
+                .iter_mut()
+                .flatten()
+                .for_each(|item| item.truncate_texts(options)),
+            TestSuitesOrTestSuite::TestSuite(testsuite) => testsuite.truncate_texts(options),
+        }
+    }
+}
+
+impl TestSuite {
+    /// Truncate texts that exceed the limits. See [`TestSuitesOrTestSuite::truncate_texts`].
+    pub fn truncate_texts(&mut self, options: &TruncateOptions) {
+        options.outputs(&mut self.system_out);
+        options.outputs(&mut self.system_err);
+        if let Some(testcase) = &mut self.testcase {
+            testcase
+                .iter_mut()
+                .for_each(|item| item.truncate_texts(options));
+        }
+        if let Some(testsuite) = &mut self.testsuite {
+            testsuite
+                .iter_mut()
+                .for_each(|item| item.truncate_texts(options));
+        }
+    }
+}
+
+impl TestCase {
+    /// Truncate texts that exceed the limits. See [`TestSuitesOrTestSuite::truncate_texts`].
+    pub fn truncate_texts(&mut self, options: &TruncateOptions) {
+        options.outputs(&mut self.system_out);
+        options.outputs(&mut self.system_err);
+        [&mut self.skipped, &mut self.error, &mut self.failure]
+            .into_iter()
+            .flatten()
+            .flatten()
+            .for_each(|detail: &mut Detail| {
+                options.detail(&mut detail.message);
+                options.detail(&mut detail.inner);
+            });
+        [
+            &mut self.flaky_failure,
+            &mut self.flaky_error,
+            &mut self.rerun_failure,
+            &mut self.rerun_error,
+        ]
+        .into_iter()
+        .flatten()
+        .flatten()
+        .for_each(|rerun: &mut Rerun| {
+            options.detail(&mut rerun.message);
+            options.detail(&mut rerun.stack_trace);
+            options.outputs(&mut rerun.system_out);
+            options.outputs(&mut rerun.system_err);
+        });
+    }
+}
@@ -0,0 +1,362 @@
+//! Serialize the structs back to JUnit XML.
+//!
+//! The serde attributes of the structs are for JSON, so the XML is written with quick-xml events
+//! in the element order of the JUnit XML Schema.
+//! Items in `extra` of the lossless mode are written back as attributes and child elements.
+
+use crate::{Detail, Properties, Rerun, TestCase, TestSuite, TestSuites, TestSuitesOrTestSuite};
+use quick_xml::events::attributes::Attribute;
+use quick_xml::events::{BytesCData, BytesDecl, BytesEnd, BytesStart, BytesText, Event};
+use quick_xml::name::QName;
+use quick_xml::Writer;
+use serde_json::{Map, Value};
+use std::borrow::Cow;
+use std::fmt::Display;
+use std::io;
+
+type Extra<'a> = Option<&'a Map<String, Value>>;
+
+/// Escape an attribute value. Line breaks and tabs are escaped too, because XML parsers normalize them to spaces.
+fn escape_attribute(value: &str) -> String {
+    quick_xml::escape::escape(value)
+        .replace('\n', "&#xA;")
+        .replace('\r', "&#xD;")
+        .replace('\t', "&#x9;")
+}
+
+fn push_attribute<T: Display>(start: &mut BytesStart, name: &str, value: &Option<T>) {
+    if let Some(value) = value {
+        start.push_attribute(Attribute {
+            key: QName(name.as_bytes()),
+            value: Cow::Owned(escape_attribute(&value.to_string()).into_bytes()),
+        });
+    }
+}
+
+/// Push attributes with `@` prefix in `extra`.
+fn push_extra_attributes(start: &mut BytesStart, extra: Extra) {
+    for (key, value) in extra.into_iter().flatten() {
+        if let (Some(name), Value::String(value)) = (key.strip_prefix('@'), value) {
+            push_attribute(start, name, &Some(value));
+        }
+    }
+}
+
+/// Write text as CDATA when it has leading or trailing whitespace, because it is trimmed in escaped text.
+fn write_text<W: io::Write>(writer: &mut Writer<W>, text: &str) -> io::Result<()> {
+    if text.trim() != text && !text.contains("]]>") {
+        writer.write_event(Event::CData(BytesCData::new(text)))
+    } else {
+        writer.write_event(Event::Text(BytesText::new(text)))
+    }
+}
+
+fn write_text_element<W: io::Write>(
+    writer: &mut Writer<W>,
+    name: &str,
+    text: &str,
+) -> io::Result<()> {
+    writer.write_event(Event::Start(BytesStart::new(name)))?;
+    write_text(writer, text)?;
+    writer.write_event(Event::End(BytesEnd::new(name)))
+}
+
+fn write_text_elements<W: io::Write>(
+    writer: &mut Writer<W>,
+    name: &str,
+    texts: &Option<Vec<String>>,
+) -> io::Result<()> {
+    for text in texts.iter().flatten() {
+        write_text_element(writer, name, text)?;
+    }
+    Ok(())
+}
+
+/// Write an element that has attributes, children written by `children` and text.
+fn write_element<W: io::Write>(
+    writer: &mut Writer<W>,
+    start: BytesStart,
+    text: Option<&str>,
+    has_children: bool,
+    children: impl FnOnce(&mut Writer<W>) -> io::Result<()>,
+) -> io::Result<()> {
+    let text = text.filter(|text| !text.is_empty());
+    if text.is_none() && !has_children {
+        return writer.write_event(Event::Empty(start));
+    }
+    let end = start.to_end().into_owned();
+    writer.write_event(Event::Start(start))?;
+    if let Some(text) = text {
+        write_text(writer, text)?;
+    }
+    children(writer)?;
+    writer.write_event(Event::End(end))
+}
+
+/// Write an element of `extra` that is a string of its text or an object of its attributes, children and `"$text"`.
+fn write_extra_element<W: io::Write>(
+    writer: &mut Writer<W>,
+    name: &str,
+    value: &Value,
+) -> io::Result<()> {
+    let mut start = BytesStart::new(name);
+    match value {
+        Value::Object(object) => {
+            let extra = Some(object);
+            push_extra_attributes(&mut start, extra);
+            let text = object.get("$text").and_then(|text| text.as_str());
+            write_element(writer, start, text, has_extra_elements(extra), |writer| {
+                write_extra_elements(writer, extra)
+            })
+        }
+        Value::String(text) => write_element(writer, start, Some(text), false, |_| Ok(())),
+        _ => write_element(writer, start, None, false, |_| Ok(())),
+    }
+}
+
+fn has_extra_elements(extra: Extra) -> bool {
+    extra
+        .into_iter()
+        .flatten()
+        .any(|(key, _)| !key.starts_with('@') && key != "$text")
+}
+
+/// Write child elements in `extra`, that are stored as array.
+fn write_extra_elements<W: io::Write>(writer: &mut Writer<W>, extra: Extra) -> io::Result<()> {
+    for (key, value) in extra.into_iter().flatten() {
+        if key.starts_with('@') || key == "$text" {
+            continue;
+        }
+        match value {
+            Value::Array(values) => {
+                for value in values {
+                    write_extra_element(writer, key, value)?;
+                }
+            }
+            value => write_extra_element(writer, key, value)?,
+        }
+    }
+    Ok(())
+}
+
+fn write_properties<W: io::Write>(
+    writer: &mut Writer<W>,
+    properties: &Option<Properties>,
+) -> io::Result<()> {
+    let Some(properties) = properties else {
+        return Ok(());
+    };
+    let start = BytesStart::new("properties");
+    write_element(
+        writer,
+        start,
+        None,
+        properties.property.is_some(),
+        |writer| {
+            for property in properties.property.iter().flatten() {
+                let mut start = BytesStart::new("property");
+                push_attribute(&mut start, "name", &property.name);
+                push_attribute(&mut start, "value", &property.value);
+                writer.write_event(Event::Empty(start))?;
+            }
+            Ok(())
+        },
+    )
+}
+
+fn write_details<W: io::Write>(
+    writer: &mut Writer<W>,
+    name: &str,
+    details: &Option<Vec<Detail>>,
+) -> io::Result<()> {
+    for detail in details.iter().flatten() {
+        let mut start = BytesStart::new(name);
+        push_attribute(&mut start, "message", &detail.message);
+        push_attribute(&mut start, "type", &detail.r#type);
+        push_extra_attributes(&mut start, detail.extra.as_ref());
+        write_element(
+            writer,
+            start,
+            detail.inner.as_deref(),
+            has_extra_elements(detail.extra.as_ref()),
+            |writer| write_extra_elements(writer, detail.extra.as_ref()),
+        )?;
+    }
+    Ok(())
+}
+
+fn write_reruns<W: io::Write>(
+    writer: &mut Writer<W>,
+    name: &str,
+    reruns: &Option<Vec<Rerun>>,
+) -> io::Result<()> {
+    for rerun in reruns.iter().flatten() {
+        let mut start = BytesStart::new(name);
+        push_attribute(&mut start, "message", &rerun.message);
+        push_attribute(&mut start, "type", &rerun.r#type);
+        push_attribute(&mut start, "timestamp", &rerun.timestamp);
+        push_attribute(&mut start, "time", &rerun.time);
+        let has_children =
+            rerun.stack_trace.is_some() || rerun.system_out.is_some() || rerun.system_err.is_some();
+        write_element(writer, start, None, has_children, |writer| {
+            if let Some(text) = &rerun.stack_trace {
+                write_text_element(writer, "stackTrace", text)?;
+            }
+            write_text_elements(writer, "system-out", &rerun.system_out)?;
+            write_text_elements(writer, "system-err", &rerun.system_err)
+        })?;
+    }
+    Ok(())
+}
+
+fn write_testcase<W: io::Write>(writer: &mut Writer<W>, testcase: &TestCase) -> io::Result<()> {
+    let mut start = BytesStart::new("testcase");
+    push_attribute(&mut start, "name", &testcase.name);
+    push_attribute(&mut start, "classname", &testcase.classname);
+    push_attribute(&mut start, "assertions", &testcase.assertions);
+    push_attribute(&mut start, "time", &testcase.time);
+    push_attribute(&mut start, "status", &testcase.status);
+    push_attribute(&mut start, "file", &testcase.file);
+    push_attribute(&mut start, "line", &testcase.line);
+    push_attribute(&mut start, "timestamp", &testcase.timestamp);
+    push_attribute(&mut start, "hostname", &testcase.hostname);
+    push_extra_attributes(&mut start, testcase.extra.as_ref());
+
+    let has_children = testcase.properties.is_some()
+        || testcase.skipped.is_some()
+        || testcase.error.is_some()
+        || testcase.failure.is_some()
+        || testcase.flaky_failure.is_some()
+        || testcase.flaky_error.is_some()
+        || testcase.rerun_failure.is_some()
+        || testcase.rerun_error.is_some()
+        || testcase.system_out.is_some()
+        || testcase.system_err.is_some()
+        || has_extra_elements(testcase.extra.as_ref());
+    write_element(writer, start, None, has_children, |writer| {
+        write_properties(writer, &testcase.properties)?;
+        write_details(writer, "skipped", &testcase.skipped)?;
+        write_details(writer, "error", &testcase.error)?;
+        write_details(writer, "failure", &testcase.failure)?;
+        write_reruns(writer, "flakyFailure", &testcase.flaky_failure)?;
+        write_reruns(writer, "flakyError", &testcase.flaky_error)?;
+        write_reruns(writer, "rerunFailure", &testcase.rerun_failure)?;
+        write_reruns(writer, "rerunError", &testcase.rerun_error)?;
+        write_text_elements(writer, "system-out", &testcase.system_out)?;
+        write_text_elements(writer, "system-err", &testcase.system_err)?;
+        write_extra_elements(writer, testcase.extra.as_ref())
+    })
+}
+
+fn write_testsuite<W: io::Write>(writer: &mut Writer<W>, testsuite: &TestSuite) -> io::Result<()> {
+    let mut start = BytesStart::new("testsuite");
+    push_attribute(&mut start, "name", &testsuite.name);
+    push_attribute(&mut start, "tests", &testsuite.tests);
+    push_attribute(&mut start, "failures", &testsuite.failures);
+    push_attribute(&mut start, "errors", &testsuite.errors);
+    push_attribute(&mut start, "group", &testsuite.group);
+    push_attribute(&mut start, "time", &testsuite.time);
+    push_attribute(&mut start, "disabled", &testsuite.disabled);
+    push_attribute(&mut start, "skipped", &testsuite.skipped);
+    push_attribute(&mut start, "timestamp", &testsuite.timestamp);
+    push_attribute(&mut start, "hostname", &testsuite.hostname);
+    push_attribute(&mut start, "id", &testsuite.id);
+    push_attribute(&mut start, "package", &testsuite.package);
+    push_attribute(&mut start, "file", &testsuite.file);
+    push_attribute(&mut start, "log", &testsuite.log);
+    push_attribute(&mut start, "url", &testsuite.url);
+    push_extra_attributes(&mut start, testsuite.extra.as_ref());
+
+    let has_children = testsuite.properties.is_some()
+        || testsuite.testcase.is_some()
+        || testsuite.testsuite.is_some()
+        || testsuite.system_out.is_some()
+        || testsuite.system_err.is_some()
+        || has_extra_elements(testsuite.extra.as_ref());
+    write_element(writer, start, None, has_children, |writer| {
+        write_properties(writer, &testsuite.properties)?;
+        for testcase in testsuite.testcase.iter().flatten() {
+            write_testcase(writer, testcase)?;
+        }
+        for nested in testsuite.testsuite.iter().flatten() {
+            write_testsuite(writer, nested)?;
+        }
+        write_text_elements(writer, "system-out", &testsuite.system_out)?;
+        write_text_elements(writer, "system-err", &testsuite.system_err)?;
+        write_extra_elements(writer, testsuite.extra.as_ref())
+    })
+}
+
+fn write_testsuites<W: io::Write>(
+    writer: &mut Writer<W>,
+    testsuites: &TestSuites,
+) -> io::Result<()> {
+    let mut start = BytesStart::new("testsuites");
+    push_attribute(&mut start, "name", &testsuites.name);
+    push_attribute(&mut start, "time", &testsuites.time);
+    push_attribute(&mut start, "tests", &testsuites.tests);
+    push_attribute(&mut start, "failures", &testsuites.failures);
+    push_attribute(&mut start, "errors", &testsuites.errors);
+    push_attribute(&mut start, "skipped", &testsuites.skipped);
+    push_attribute(&mut start, "assertions", &testsuites.assertions);
+    push_attribute(&mut start, "timestamp", &testsuites.timestamp);
+    push_extra_attributes(&mut start, testsuites.extra.as_ref());
+
+    let has_children =
+        testsuites.testsuite.is_some() || has_extra_elements(testsuites.extra.as_ref());
+    write_element(writer, start, None, has_children, |writer| {
+        for testsuite in testsuites.testsuite.iter().flatten() {
+            write_testsuite(writer, testsuite)?;
+        }
+        write_extra_elements(writer, testsuites.extra.as_ref())
+    })
+}
+
+/// Serialize to JUnit XML and write it to the writer.
+///
+/// # Examples
+/// ```
+/// use junit2json;
+///
+/// let xml = r#"
+///     <?xml version="1.0" encoding="UTF-8"?>
+///     <testsuites>
+///         <testsuite name="suite1" tests="1">
+///             <testcase name="case1" />
+///         </testsuite>
+///     </testsuites>
+/// "#;
+/// let testsuites = junit2json::from_str(xml).unwrap();
+/// junit2json::to_writer(std::io::stdout(), &testsuites).unwrap();
+/// ```
+pub fn to_writer<W: io::Write>(writer: W, root: &TestSuitesOrTestSuite) -> io::Result<()> {
+    let mut writer = Writer::new_with_indent(writer, b' ', 2);
+    writer.write_event(Event::Decl(BytesDecl::new("1.0", Some("UTF-8"), None)))?;
+    match root {
+        TestSuitesOrTestSuite::TestSuites(testsuites) => write_testsuites(&mut writer, testsuites)?,
+        TestSuitesOrTestSuite::TestSuite(testsuite) => write_testsuite(&mut writer, testsuite)?,
+    }
+    writer.get_mut().write_all(b"\n")
+}
+
+/// Serialize to JUnit XML string.
+///
+/// # Examples
+/// ```
+/// use junit2json;
+///
+/// let xml = r#"
+///     <?xml version="1.0" encoding="UTF-8"?>
+///     <testsuite name="suite1" tests="1">
+///         <testcase name="case1" />
+///     </testsuite>
+/// "#;
+/// let testsuite = junit2json::from_str(xml).unwrap();
+/// let xml = junit2json::to_string(&testsuite).unwrap();
+/// assert_eq!(junit2json::from_str(&xml).unwrap(), testsuite);
+/// ```
+pub fn to_string(root: &TestSuitesOrTestSuite) -> io::Result<String> {
+    let mut xml = Vec::new();
+    to_writer(&mut xml, root)?;
+    String::from_utf8(xml).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
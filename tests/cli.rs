@@ -0,0 +1,59 @@
+use clap::Parser;
+use junit2json::cli::Args;
+use pretty_assertions::assert_eq;
+use std::path::PathBuf;
+
+fn input_files(paths: &[&str]) -> Vec<PathBuf> {
+    let args = Args::parse_from([&["junit2json"], paths].concat());
+    args.input_files().unwrap()
+}
+
+#[test]
+fn expand_glob_and_directory() {
+    let expected = input_files(&[
+        "tests/fixtures/jest-failure.xml",
+        "tests/fixtures/jest-success.xml",
+    ]);
+    assert_eq!(input_files(&["tests/fixtures/jest-*.xml"]), expected);
+
+    let all = input_files(&["tests/fixtures"]);
+    assert!(all.windows(2).all(|w| w[0] < w[1]));
+    assert!(all.iter().all(|path| path.extension().unwrap() == "xml"));
+    assert!(all.contains(&expected[0]));
+}
+
+#[test]
+fn tags_path() {
+    let args = Args::parse_from(["junit2json", "tests/fixtures/jest-failure.xml"]);
+    assert!(!args.tags_path(&args.input_files().unwrap()));
+    let args = Args::parse_from([
+        "junit2json",
+        "--output",
+        "ndjson",
+        "tests/fixtures/jest-failure.xml",
+    ]);
+    assert!(args.tags_path(&args.input_files().unwrap()));
+    let args = Args::parse_from(["junit2json", "tests/fixtures/jest-f*.xml"]);
+    assert!(args.tags_path(&args.input_files().unwrap()));
+}
+
+#[test]
+fn no_match() {
+    let args = Args::parse_from(["junit2json", "tests/fixtures/*.json"]);
+    assert!(args.input_files().is_err());
+}
+
+#[test]
+fn stdin() {
+    let args = Args::parse_from(["junit2json"]);
+    assert_eq!(args.input_files().unwrap(), vec![PathBuf::from("-")]);
+    assert!(!args.tags_path(&args.input_files().unwrap()));
+    let args = Args::parse_from(["junit2json", "-", "tests/fixtures/jest-success.xml"]);
+    assert_eq!(
+        args.input_files().unwrap(),
+        vec![
+            PathBuf::from("-"),
+            PathBuf::from("tests/fixtures/jest-success.xml")
+        ]
+    );
+}
@@ -0,0 +1,37 @@
+//! Fixtures shared by the integration tests.
+#![allow(dead_code)]
+
+/// `system-out` and `system-err` of a testsuite and a testcase
+pub fn system_out_fixture() -> &'static str {
+    r#"
+      <?xml version="1.0" encoding="UTF-8"?>
+      <testsuites>
+          <testsuite name="suite1">
+              <system-out>system out text</system-out>
+              <system-err>system error text</system-err>
+              <testcase name="case1">
+                <system-out>system out text</system-out>
+                <system-err>system error text</system-err>
+              </testcase>
+          </testsuite>
+      </testsuites>
+  "#
+}
+
+/// Unknown attributes and elements that only the lossless mode keeps
+pub fn unknown_items_fixture() -> &'static str {
+    r#"
+      <?xml version="1.0" encoding="UTF-8"?>
+      <testsuites name="suites1" uuid="d241b926">
+          <testsuite name="suite1" custom="foo">
+              <testcase name="case1" retries="2">
+                <failure message="failure1" attempt="1">failure text</failure>
+                <custom-tag>custom text</custom-tag>
+                <custom-tag key="value">
+                    <nested>nested text</nested>
+                </custom-tag>
+              </testcase>
+          </testsuite>
+      </testsuites>
+  "#
+}
@@ -0,0 +1,139 @@
+use junit2json::*;
+use pretty_assertions::assert_eq;
+
+const XML: &str = r#"
+  <?xml version="1.0" encoding="UTF-8"?>
+  <testsuites tests="9" failures="0">
+      <testsuite name="suite1" tests="2" failures="1" errors="1">
+          <testcase name="case1" time="0.1"><failure /></testcase>
+          <testcase name="case2" time="0.2"><error /></testcase>
+          <testcase name="case3" time="0.3"><skipped /></testcase>
+      </testsuite>
+      <testsuite name="suite2" time="1.5">
+          <testsuite name="nested" tests="1" failures="1">
+              <testcase name="case4"><failure /></testcase>
+          </testsuite>
+      </testsuite>
+  </testsuites>
+"#;
+
+#[test]
+// Test that declared counters that disagree with testcases are reported, parent first
+fn validate_counters() {
+    let testsuites = from_str(XML).unwrap();
+    let actual = testsuites
+        .validate_counters()
+        .iter()
+        .map(|mismatch| mismatch.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        actual,
+        vec![
+            r#"tests="9" but 4 are counted from testcases (testsuites)"#,
+            r#"failures="0" but 2 are counted from testcases (testsuites)"#,
+            r#"tests="2" but 3 are counted from testcases (testsuites/testsuite[1])"#,
+        ]
+    );
+}
+
+#[test]
+// Test that counters are recomputed and time is the sum of testcases
+fn recompute_counters() {
+    let mut testsuites = from_str(XML).unwrap();
+    testsuites.recompute_counters();
+    assert_eq!(testsuites.validate_counters(), vec![]);
+
+    let TestSuitesOrTestSuite::TestSuites(testsuites) = testsuites else {
+        panic!("not testsuites");
+    };
+    assert_eq!(
+        (
+            testsuites.tests,
+            testsuites.failures,
+            testsuites.errors,
+            testsuites.skipped
+        ),
+        (Some(4), Some(2), Some(1), Some(1))
+    );
+    assert_eq!(testsuites.time, Some(2.1));
+    let testsuite = testsuites.testsuite.unwrap();
+    assert_eq!(testsuite[0].time, Some(0.6));
+    // Declared time is kept without timed testcases
+    assert_eq!(testsuite[1].time, Some(1.5));
+    assert_eq!(testsuite[1].tests, Some(1));
+    assert_eq!(testsuite[1].testsuite.as_ref().unwrap()[0].time, None);
+}
+
+#[test]
+// Test that disabled and time shorter than a testcase are reported
+fn validate_disabled_and_time() {
+    let xml = r#"
+      <testsuites time="0.2">
+          <testsuite name="suite1" tests="2" disabled="0" time="0.5">
+              <testcase name="case1" time="1.2" />
+              <testcase name="case2" status="notrun" />
+          </testsuite>
+          <testsuite name="suite2" tests="1" time="0.1">
+              <testcase name="case3" time="0.1004" />
+          </testsuite>
+      </testsuites>
+    "#;
+    let testsuites = from_str(xml).unwrap();
+    let actual = testsuites
+        .validate_counters()
+        .iter()
+        .map(|mismatch| mismatch.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        actual,
+        vec![
+            r#"time="0.2" but a testcase or testsuite takes 0.5 (testsuites)"#,
+            r#"disabled="0" but 1 are counted from testcases (testsuites/testsuite[1])"#,
+            r#"time="0.5" but a testcase or testsuite takes 1.2 (testsuites/testsuite[1])"#,
+        ]
+    );
+}
+
+#[test]
+// Test that disabled and time are recomputed from the kept testcases
+fn recompute_disabled_and_time_after_filter() {
+    let xml = r#"
+      <testsuite name="suite1" tests="3" time="5.0">
+          <testcase name="case1" time="1.5" />
+          <testcase name="case2" time="2.5"><failure /></testcase>
+          <testcase name="case3" status="disabled" />
+      </testsuite>
+    "#;
+    let mut testsuites = from_str(xml).unwrap();
+    testsuites.recompute_counters();
+    let TestSuitesOrTestSuite::TestSuite(testsuite) = &testsuites else {
+        panic!("not testsuite");
+    };
+    assert_eq!(
+        (testsuite.tests, testsuite.disabled, testsuite.time),
+        (Some(3), Some(1), Some(4.0))
+    );
+
+    testsuites.filter_testcases(&TestCaseFilter {
+        outcomes: vec![Outcome::Failed],
+        ..Default::default()
+    });
+    testsuites.recompute_counters();
+    let TestSuitesOrTestSuite::TestSuite(testsuite) = &testsuites else {
+        panic!("not testsuite");
+    };
+    assert_eq!(
+        (testsuite.tests, testsuite.disabled, testsuite.time),
+        (Some(1), Some(0), Some(2.5))
+    );
+}
+
+#[test]
+// Test that the fixtures have no mismatches
+fn fixtures_are_consistent() {
+    insta::glob!("fixtures/*.xml", |path| {
+        let xml = std::fs::read_to_string(path).unwrap();
+        let testsuites = from_str(&xml).unwrap();
+        assert_eq!(testsuites.validate_counters(), vec![], "{}", path.display());
+    })
+}
@@ -73,10 +73,10 @@ fn testcase_failure_has_inner() {
             testsuite: Some(vec![TestSuite {
                 tests: Some(1),
                 testcase: Some(vec![TestCase {
-                    failure: Some(Detail {
+                    failure: Some(vec![Detail {
                         inner: Some("inner text".to_string()),
                         ..Default::default()
-                    }),
+                    }]),
                     ..Default::default()
                 }]),
                 ..Default::default()
@@ -107,10 +107,10 @@ fn skipped_testcase() {
                 tests: Some(1),
                 skipped: Some(1),
                 testcase: Some(vec![TestCase {
-                    skipped: Some(Detail {
+                    skipped: Some(vec![Detail {
                         message: Some("skip reason".to_string()),
                         ..Default::default()
-                    }),
+                    }]),
                     ..Default::default()
                 }]),
                 ..Default::default()
@@ -271,3 +271,208 @@ fn some_testsuite_property_are_empty() {
         })
     );
 }
+
+#[test]
+// Test when testsuite has nested testsuite
+fn nested_testsuite() {
+    let xml = r#"
+          <?xml version="1.0" encoding="UTF-8"?>
+          <testsuites>
+              <testsuite name="parent">
+                  <testcase name="case1"/>
+                  <testsuite name="child">
+                      <testcase name="case2"/>
+                  </testsuite>
+                  <testcase name="case3"/>
+              </testsuite>
+          </testsuites>
+      "#;
+    let actual = from_str(xml);
+    assert_eq!(
+        actual.unwrap(),
+        TestSuitesOrTestSuite::TestSuites(TestSuites {
+            testsuite: Some(vec![TestSuite {
+                name: Some("parent".to_string()),
+                testcase: Some(vec![
+                    TestCase {
+                        name: Some("case1".to_string()),
+                        ..Default::default()
+                    },
+                    TestCase {
+                        name: Some("case3".to_string()),
+                        ..Default::default()
+                    }
+                ]),
+                testsuite: Some(vec![TestSuite {
+                    name: Some("child".to_string()),
+                    testcase: Some(vec![TestCase {
+                        name: Some("case2".to_string()),
+                        ..Default::default()
+                    }]),
+                    ..Default::default()
+                }]),
+                ..Default::default()
+            }]),
+            ..Default::default()
+        })
+    );
+}
+
+#[test]
+// Test when testcase has multiple failures and errors
+fn testcase_has_multiple_failures() {
+    let xml = r#"
+          <?xml version="1.0" encoding="UTF-8"?>
+          <testsuites>
+              <testsuite>
+                  <testcase>
+                      <failure message="failure1" />
+                      <error message="error1" />
+                      <failure message="failure2" />
+                  </testcase>
+              </testsuite>
+          </testsuites>
+      "#;
+    let actual = from_str(xml);
+    assert_eq!(
+        actual.unwrap(),
+        TestSuitesOrTestSuite::TestSuites(TestSuites {
+            testsuite: Some(vec![TestSuite {
+                testcase: Some(vec![TestCase {
+                    error: Some(vec![Detail {
+                        message: Some("error1".to_string()),
+                        ..Default::default()
+                    }]),
+                    failure: Some(vec![
+                        Detail {
+                            message: Some("failure1".to_string()),
+                            ..Default::default()
+                        },
+                        Detail {
+                            message: Some("failure2".to_string()),
+                            ..Default::default()
+                        }
+                    ]),
+                    ..Default::default()
+                }]),
+                ..Default::default()
+            }]),
+            ..Default::default()
+        })
+    );
+}
+
+#[test]
+// Test when testcase has flakyFailure and rerunError
+fn testcase_has_flaky_and_rerun() {
+    let xml = r#"
+          <?xml version="1.0" encoding="UTF-8"?>
+          <testsuites>
+              <testsuite>
+                  <testcase name="flaky">
+                      <flakyFailure message="flaky1" type="AssertionError" time="0.1">
+                          <stackTrace>stack trace</stackTrace>
+                          <system-out>system out text</system-out>
+                      </flakyFailure>
+                  </testcase>
+                  <testcase name="broken">
+                      <error message="error1" />
+                      <rerunError message="error2">
+                          <system-err>system error text</system-err>
+                      </rerunError>
+                  </testcase>
+              </testsuite>
+          </testsuites>
+      "#;
+    let actual = from_str(xml).unwrap();
+    assert_eq!(
+        actual,
+        TestSuitesOrTestSuite::TestSuites(TestSuites {
+            testsuite: Some(vec![TestSuite {
+                testcase: Some(vec![
+                    TestCase {
+                        name: Some("flaky".to_string()),
+                        flaky_failure: Some(vec![Rerun {
+                            message: Some("flaky1".to_string()),
+                            r#type: Some("AssertionError".to_string()),
+                            time: Some(0.1),
+                            stack_trace: Some("stack trace".to_string()),
+                            system_out: Some(vec!["system out text".to_string()]),
+                            ..Default::default()
+                        }]),
+                        ..Default::default()
+                    },
+                    TestCase {
+                        name: Some("broken".to_string()),
+                        error: Some(vec![Detail {
+                            message: Some("error1".to_string()),
+                            ..Default::default()
+                        }]),
+                        rerun_error: Some(vec![Rerun {
+                            message: Some("error2".to_string()),
+                            system_err: Some(vec!["system error text".to_string()]),
+                            ..Default::default()
+                        }]),
+                        ..Default::default()
+                    }
+                ]),
+                ..Default::default()
+            }]),
+            ..Default::default()
+        })
+    );
+
+    let flaky = actual.flaky_testcases();
+    assert_eq!(flaky.len(), 1);
+    assert_eq!(flaky[0].name, Some("flaky".to_string()));
+}
+
+#[test]
+// Test when testsuites and testcase have standard attributes and testcase.property
+fn testsuites_and_testcase_have_standard_fields() {
+    let xml = r#"
+          <?xml version="1.0" encoding="UTF-8"?>
+          <testsuites skipped="1" assertions="3" timestamp="2024-03-02T10:11:12">
+              <testsuite>
+                  <testcase timestamp="2024-03-02T10:11:13" hostname="runner-01">
+                      <properties>
+                          <property name="hello" value="bonjour"/>
+                          <property/>
+                      </properties>
+                  </testcase>
+              </testsuite>
+          </testsuites>
+      "#;
+    let actual = from_str(xml);
+    assert_eq!(
+        actual.unwrap(),
+        TestSuitesOrTestSuite::TestSuites(TestSuites {
+            skipped: Some(1),
+            assertions: Some(3),
+            timestamp: Some("2024-03-02T10:11:12".to_string()),
+            testsuite: Some(vec![TestSuite {
+                testcase: Some(vec![TestCase {
+                    timestamp: Some("2024-03-02T10:11:13".to_string()),
+                    hostname: Some("runner-01".to_string()),
+                    properties: Some(Properties {
+                        property: Some(vec![Property {
+                            name: Some("hello".to_string()),
+                            value: Some("bonjour".to_string()),
+                        }]),
+                    }),
+                    ..Default::default()
+                }]),
+                ..Default::default()
+            }]),
+            ..Default::default()
+        })
+    );
+}
+
+#[test]
+// Test that from_read accepts any io::Read without io::BufReader
+fn from_read_bytes() {
+    let xml = r#"<testsuite name="suite1"><testcase name="case1" /></testsuite>"#;
+    let actual = from_read(xml.as_bytes());
+    assert_eq!(actual.unwrap(), from_str(xml).unwrap());
+}
@@ -0,0 +1,169 @@
+use junit2json::*;
+use pretty_assertions::assert_eq;
+
+#[test]
+// Test when input is not JUnit XML
+fn unexpected_root() {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<unrecognized />
+"#;
+    let actual = from_str(xml).unwrap_err();
+    match actual {
+        Error::UnexpectedRoot { name, location } => {
+            assert_eq!(name, "unrecognized");
+            assert_eq!(
+                location,
+                Location {
+                    offset: 39,
+                    line: 2,
+                    column: 1,
+                    path: "unrecognized".to_string(),
+                }
+            );
+        }
+        _ => panic!("unexpected error: {:?}", actual),
+    }
+}
+
+#[test]
+// Test when input read from a reader is not JUnit XML
+fn unexpected_root_from_reader() {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<unrecognized />
+"#;
+    let actual = from_reader(std::io::BufReader::new(xml.as_bytes())).unwrap_err();
+    match actual {
+        Error::UnexpectedRoot { name, location } => {
+            assert_eq!(name, "unrecognized");
+            assert_eq!(
+                location,
+                Location {
+                    offset: 39,
+                    line: 2,
+                    column: 1,
+                    path: "unrecognized".to_string(),
+                }
+            );
+        }
+        _ => panic!("unexpected error: {:?}", actual),
+    }
+}
+
+#[test]
+// Test when testcase.time is not a number
+fn invalid_number() {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites>
+    <testsuite name="suite1">
+        <testcase name="case1" time="0.1" />
+    </testsuite>
+    <testsuite name="suite2">
+        <testcase name="case1" time="0.1" />
+        <testcase name="case2" time="N/A" />
+    </testsuite>
+</testsuites>
+"#;
+    let actual = from_str(xml).unwrap_err();
+    match actual {
+        Error::InvalidNumber {
+            ref value,
+            ref location,
+            ..
+        } => {
+            assert_eq!(value, "N/A");
+            assert_eq!(location.line, 8);
+            assert_eq!(location.column, 9);
+            assert_eq!(
+                location.path,
+                "testsuites/testsuite[2]/testcase[2]/@time".to_string()
+            );
+        }
+        _ => panic!("unexpected error: {:?}", actual),
+    }
+    assert!(actual
+        .to_string()
+        .contains("line 8, column 9 (testsuites/testsuite[2]/testcase[2]/@time)"));
+}
+
+#[test]
+// Test when testcase.time read from a reader is not a number
+fn invalid_number_from_reader() {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites>
+    <testsuite name="suite1">
+        <testcase name="case1" time="0.1" />
+    </testsuite>
+    <testsuite name="suite2">
+        <testcase name="case1" time="0.1" />
+        <testcase name="case2" time="N/A" />
+    </testsuite>
+</testsuites>
+"#;
+    let actual = from_reader(std::io::BufReader::new(xml.as_bytes())).unwrap_err();
+    match actual {
+        Error::InvalidNumber {
+            ref value,
+            ref location,
+            ..
+        } => {
+            assert_eq!(value, "N/A");
+            assert_eq!(location.line, 8);
+            assert_eq!(location.column, 9);
+            assert_eq!(
+                location.path,
+                "testsuites/testsuite[2]/testcase[2]/@time".to_string()
+            );
+        }
+        _ => panic!("unexpected error: {:?}", actual),
+    }
+}
+
+#[test]
+// Test when XML is not well-formed
+fn syntax_error() {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites>
+    <testsuite name="suite1">
+        <testcase name="case1">
+    </testsuite>
+</testsuites>
+"#;
+    let actual = from_str(xml).unwrap_err();
+    match actual {
+        Error::Syntax { ref location, .. } => {
+            assert_eq!(location.line, 5);
+            assert_eq!(location.path, "testsuites/testsuite[1]/testcase[1]");
+        }
+        _ => panic!("unexpected error: {:?}", actual),
+    }
+}
+
+#[test]
+// Test when XML read from a reader is not well-formed
+fn syntax_error_from_reader() {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites>
+    <testsuite name="suite1">
+        <testcase name="case1" />
+        <testcase name="case2" time=0.1 />
+    </testsuite>
+</testsuites>
+"#;
+    let actual = from_reader(std::io::BufReader::new(xml.as_bytes())).unwrap_err();
+    match actual {
+        Error::Syntax { ref location, .. } => {
+            assert_eq!(location.line, 5);
+            assert_eq!(location.path, "testsuites/testsuite[1]/testcase[2]");
+        }
+        _ => panic!("unexpected error: {:?}", actual),
+    }
+}
+
+#[test]
+// Test when input is not UTF-8
+fn io_error() {
+    let bytes: &[u8] = b"<testsuites name=\"\xff\"></testsuites>";
+    let actual = from_reader(std::io::BufReader::new(bytes)).unwrap_err();
+    assert!(matches!(actual, Error::Io(_)));
+    assert_eq!(actual.location(), None);
+}
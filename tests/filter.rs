@@ -0,0 +1,135 @@
+use junit2json::*;
+use pretty_assertions::assert_eq;
+use regex::Regex;
+
+const XML: &str = r#"
+  <?xml version="1.0" encoding="UTF-8"?>
+  <testsuites tests="5" failures="2">
+      <testsuite name="suite1">
+          <testcase name="case1" classname="a.A" file="src/a/a.rs" time="0.1" />
+          <testcase name="case2" classname="a.A" file="src/a/a.rs" time="2.0"><failure /></testcase>
+      </testsuite>
+      <testsuite name="suite2">
+          <testsuite name="nested">
+              <testcase name="case3" classname="b.B" file="src/b.rs" time="3.0"><skipped /></testcase>
+          </testsuite>
+          <testcase name="case4" classname="b.B" file="src/b.rs" time="0.5"><error /></testcase>
+      </testsuite>
+      <testsuite name="empty" />
+  </testsuites>
+"#;
+
+/// Names of the testsuites and testcases that are kept.
+fn kept(filter: &TestCaseFilter) -> Vec<String> {
+    fn collect(testsuite: &TestSuite, names: &mut Vec<String>) {
+        names.push(testsuite.name.clone().unwrap());
+        for testcase in testsuite.testcase.iter().flatten() {
+            names.push(testcase.name.clone().unwrap());
+        }
+        for nested in testsuite.testsuite.iter().flatten() {
+            collect(nested, names);
+        }
+    }
+    let mut testsuites = from_str(XML).unwrap();
+    testsuites.filter_testcases(filter);
+    let TestSuitesOrTestSuite::TestSuites(testsuites) = testsuites else {
+        panic!("not testsuites");
+    };
+    let mut names = vec![];
+    for testsuite in testsuites.testsuite.iter().flatten() {
+        collect(testsuite, &mut names);
+    }
+    names
+}
+
+#[test]
+// Test that empty filter keeps everything
+fn empty_filter() {
+    let filter = TestCaseFilter::default();
+    assert!(filter.is_empty());
+    assert_eq!(
+        kept(&filter),
+        vec!["suite1", "case1", "case2", "suite2", "case4", "nested", "case3", "empty"]
+    );
+}
+
+#[test]
+// Test that suites that become empty are removed
+fn filter_by_outcome() {
+    let filter = TestCaseFilter {
+        outcomes: vec![Outcome::Failed, Outcome::Errored],
+        ..Default::default()
+    };
+    assert_eq!(
+        kept(&filter),
+        vec!["suite1", "case2", "suite2", "case4", "empty"]
+    );
+}
+
+#[test]
+fn filter_by_name_and_classname() {
+    let filter = TestCaseFilter {
+        name: Some(Regex::new("[13]$").unwrap()),
+        ..Default::default()
+    };
+    assert_eq!(
+        kept(&filter),
+        vec!["suite1", "case1", "suite2", "nested", "case3", "empty"]
+    );
+    let filter = TestCaseFilter {
+        name: Some(Regex::new("[13]$").unwrap()),
+        classname: Some(Regex::new(r"^b\.").unwrap()),
+        ..Default::default()
+    };
+    assert_eq!(kept(&filter), vec!["suite2", "nested", "case3", "empty"]);
+}
+
+#[test]
+fn filter_by_file_and_time() {
+    let filter = TestCaseFilter {
+        file: Some(glob::Pattern::new("src/a/*.rs").unwrap()),
+        ..Default::default()
+    };
+    assert_eq!(kept(&filter), vec!["suite1", "case1", "case2", "empty"]);
+    let filter = TestCaseFilter {
+        min_time: Some(2.0),
+        ..Default::default()
+    };
+    assert_eq!(
+        kept(&filter),
+        vec!["suite1", "case2", "suite2", "nested", "case3", "empty"]
+    );
+}
+
+#[test]
+// Test that `*` of the file glob does not match across directories
+fn filter_by_nested_file() {
+    let filter = |pattern: &str| TestCaseFilter {
+        file: Some(glob::Pattern::new(pattern).unwrap()),
+        ..Default::default()
+    };
+    assert_eq!(
+        kept(&filter("src/*.rs")),
+        vec!["suite2", "case4", "nested", "case3", "empty"]
+    );
+    assert_eq!(
+        kept(&filter("src/**/*.rs")),
+        vec!["suite1", "case1", "case2", "suite2", "case4", "nested", "case3", "empty"]
+    );
+    assert_eq!(kept(&filter("*.rs")), vec!["empty"]);
+}
+
+#[test]
+// Test that counters agree with the kept testcases after recompute_counters
+fn recompute_after_filter() {
+    let mut testsuites = from_str(XML).unwrap();
+    testsuites.filter_testcases(&TestCaseFilter {
+        outcomes: vec![Outcome::Failed],
+        ..Default::default()
+    });
+    testsuites.recompute_counters();
+    let TestSuitesOrTestSuite::TestSuites(testsuites) = testsuites else {
+        panic!("not testsuites");
+    };
+    assert_eq!((testsuites.tests, testsuites.failures), (Some(1), Some(1)));
+}
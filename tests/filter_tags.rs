@@ -1,26 +1,15 @@
+#![allow(clippy::useless_vec)]
+
+mod common;
+
+use common::system_out_fixture;
 use junit2json::*;
 use pretty_assertions::assert_eq;
 
-fn create_fixture() -> &'static str {
-    r#"
-      <?xml version="1.0" encoding="UTF-8"?>
-      <testsuites>
-          <testsuite name="suite1">
-              <system-out>system out text</system-out>
-              <system-err>system error text</system-err>
-              <testcase name="case1">
-                <system-out>system out text</system-out>
-                <system-err>system error text</system-err>
-              </testcase>
-          </testsuite>
-      </testsuites>
-  "#
-}
-
 #[test]
 /// Test when --filter-tags=system-out
 fn filter_system_out() {
-    let xml = create_fixture();
+    let xml = system_out_fixture();
     let mut actual = from_str(xml).unwrap();
     actual.filter_tags(&vec![junit2json::cli::PossibleFilterTags::SystemOut]);
 
@@ -45,7 +34,7 @@ fn filter_system_out() {
 #[test]
 /// Test when --filter-tags=system-err
 fn filter_system_err() {
-    let xml = create_fixture();
+    let xml = system_out_fixture();
     let mut actual = from_str(xml).unwrap();
     actual.filter_tags(&vec![junit2json::cli::PossibleFilterTags::SystemErr]);
 
@@ -70,7 +59,7 @@ fn filter_system_err() {
 #[test]
 /// Test when --filter-tags=system-out --filter-tags=system-err
 fn filter_system_out_and_err() {
-    let xml = create_fixture();
+    let xml = system_out_fixture();
     let mut actual = from_str(xml).unwrap();
     actual.filter_tags(&vec![
         junit2json::cli::PossibleFilterTags::SystemOut,
@@ -92,3 +81,228 @@ fn filter_system_out_and_err() {
         })
     );
 }
+
+#[test]
+/// Test when --filter-tags=system-out with nested testsuite
+fn filter_system_out_nested_testsuite() {
+    let xml = r#"
+      <?xml version="1.0" encoding="UTF-8"?>
+      <testsuite name="parent">
+          <testsuite name="child">
+              <system-out>system out text</system-out>
+              <testcase name="case1">
+                <system-out>system out text</system-out>
+              </testcase>
+          </testsuite>
+      </testsuite>
+  "#;
+    let mut actual = from_str(xml).unwrap();
+    actual.filter_tags(&[junit2json::cli::PossibleFilterTags::SystemOut]);
+
+    assert_eq!(
+        actual,
+        TestSuitesOrTestSuite::TestSuite(Box::new(TestSuite {
+            name: Some("parent".to_string()),
+            testsuite: Some(vec![TestSuite {
+                name: Some("child".to_string()),
+                testcase: Some(vec![TestCase {
+                    name: Some("case1".to_string()),
+                    ..Default::default()
+                }]),
+                ..Default::default()
+            }]),
+            ..Default::default()
+        }))
+    );
+}
+
+fn create_selector_fixture() -> &'static str {
+    r#"
+      <?xml version="1.0" encoding="UTF-8"?>
+      <testsuites timestamp="2024-03-02T10:11:12">
+          <testsuite name="suite1" hostname="runner-01" timestamp="2024-03-02T10:11:12">
+              <properties>
+                  <property name="PATH" value="/usr/bin" />
+              </properties>
+              <testcase name="case1" file="src/lib.rs" hostname="runner-01">
+                  <properties>
+                      <property name="attempt" value="1" />
+                  </properties>
+                  <failure message="failed" type="AssertionError">long stack trace</failure>
+                  <flakyFailure message="flaky">
+                      <stackTrace>long stack trace</stackTrace>
+                  </flakyFailure>
+              </testcase>
+          </testsuite>
+      </testsuites>
+  "#
+}
+
+fn remove_fields(selectors: &[&str]) -> TestSuitesOrTestSuite {
+    let mut actual = from_str(create_selector_fixture()).unwrap();
+    let tags = selectors
+        .iter()
+        .map(|selector| selector.parse().unwrap())
+        .collect::<Vec<FilterTag>>();
+    actual.remove_fields(&tags);
+    actual
+}
+
+#[test]
+/// Test when --filter-tags=testsuite.properties,failure.inner,flakyFailure.stackTrace
+fn filter_scoped_selectors() {
+    let actual = remove_fields(&[
+        "testsuite.properties",
+        "failure.inner",
+        "flakyFailure.stackTrace",
+    ]);
+
+    assert_eq!(
+        actual,
+        TestSuitesOrTestSuite::TestSuites(TestSuites {
+            timestamp: Some("2024-03-02T10:11:12".to_string()),
+            testsuite: Some(vec![TestSuite {
+                name: Some("suite1".to_string()),
+                hostname: Some("runner-01".to_string()),
+                timestamp: Some("2024-03-02T10:11:12".to_string()),
+                testcase: Some(vec![TestCase {
+                    name: Some("case1".to_string()),
+                    file: Some("src/lib.rs".to_string()),
+                    hostname: Some("runner-01".to_string()),
+                    properties: Some(Properties {
+                        property: Some(vec![Property {
+                            name: Some("attempt".to_string()),
+                            value: Some("1".to_string()),
+                        }]),
+                    }),
+                    failure: Some(vec![Detail {
+                        message: Some("failed".to_string()),
+                        r#type: Some("AssertionError".to_string()),
+                        ..Default::default()
+                    }]),
+                    flaky_failure: Some(vec![Rerun {
+                        message: Some("flaky".to_string()),
+                        ..Default::default()
+                    }]),
+                    ..Default::default()
+                }]),
+                ..Default::default()
+            }]),
+            ..Default::default()
+        })
+    );
+}
+
+#[test]
+/// Test when --filter-tags=properties,hostname,timestamp,testcase.file
+fn filter_unscoped_selectors() {
+    let actual = remove_fields(&["properties", "hostname", "timestamp", "testcase.file"]);
+
+    assert_eq!(
+        actual,
+        TestSuitesOrTestSuite::TestSuites(TestSuites {
+            testsuite: Some(vec![TestSuite {
+                name: Some("suite1".to_string()),
+                testcase: Some(vec![TestCase {
+                    name: Some("case1".to_string()),
+                    failure: Some(vec![Detail {
+                        message: Some("failed".to_string()),
+                        r#type: Some("AssertionError".to_string()),
+                        inner: Some("long stack trace".to_string()),
+                        ..Default::default()
+                    }]),
+                    flaky_failure: Some(vec![Rerun {
+                        message: Some("flaky".to_string()),
+                        stack_trace: Some("long stack trace".to_string()),
+                        ..Default::default()
+                    }]),
+                    ..Default::default()
+                }]),
+                ..Default::default()
+            }]),
+            ..Default::default()
+        })
+    );
+}
+
+#[test]
+/// Test that unscoped skipped removes only the element of testcase, and scoped one removes the counter
+fn filter_skipped_element_and_counter() {
+    let xml = r#"
+      <testsuites skipped="1">
+          <testsuite name="suite1" skipped="1">
+              <testcase name="case1"><skipped message="not ready" /></testcase>
+          </testsuite>
+      </testsuites>
+    "#;
+    let skipped_testcase = TestCase {
+        name: Some("case1".to_string()),
+        skipped: Some(vec![Detail {
+            message: Some("not ready".to_string()),
+            ..Default::default()
+        }]),
+        ..Default::default()
+    };
+    let expected = |testsuites_skipped, testsuite_skipped, testcase: TestCase| {
+        TestSuitesOrTestSuite::TestSuites(TestSuites {
+            skipped: testsuites_skipped,
+            testsuite: Some(vec![TestSuite {
+                name: Some("suite1".to_string()),
+                skipped: testsuite_skipped,
+                testcase: Some(vec![testcase]),
+                ..Default::default()
+            }]),
+            ..Default::default()
+        })
+    };
+
+    let mut actual = from_str(xml).unwrap();
+    actual.remove_fields(&["skipped".parse().unwrap()]);
+    assert_eq!(
+        actual,
+        expected(
+            Some(1),
+            Some(1),
+            TestCase {
+                name: Some("case1".to_string()),
+                ..Default::default()
+            }
+        )
+    );
+
+    let mut actual = from_str(xml).unwrap();
+    actual.remove_fields(&["testsuite.skipped".parse().unwrap()]);
+    assert_eq!(actual, expected(Some(1), None, skipped_testcase.clone()));
+
+    let mut actual = from_str(xml).unwrap();
+    actual.remove_fields(&["testsuites.skipped".parse().unwrap()]);
+    assert_eq!(actual, expected(None, Some(1), skipped_testcase));
+}
+
+#[test]
+/// Test that PossibleFilterTags is the same as the unscoped selector
+fn possible_filter_tags_are_selectors() {
+    let mut expected = from_str(system_out_fixture()).unwrap();
+    expected.remove_fields(&["system-out".parse().unwrap()]);
+    let mut actual = from_str(system_out_fixture()).unwrap();
+    actual.filter_tags(&[junit2json::cli::PossibleFilterTags::SystemOut]);
+    assert_eq!(actual, expected);
+}
+
+#[test]
+/// Test that selectors of unknown elements and fields are rejected
+fn parse_selectors() {
+    let tag: FilterTag = "failure.inner".parse().unwrap();
+    assert_eq!(
+        tag,
+        FilterTag {
+            scope: Some("failure".to_string()),
+            field: "inner".to_string(),
+        }
+    );
+    assert_eq!(tag.to_string(), "failure.inner");
+    assert!("stackTrace".parse::<FilterTag>().is_ok());
+    assert!("testcase.stackTrace".parse::<FilterTag>().is_err());
+    assert!("property.name".parse::<FilterTag>().is_err());
+    assert!("unknown".parse::<FilterTag>().is_err());
+}
@@ -0,0 +1,94 @@
+use junit2json::cli::DetailShape;
+use junit2json::*;
+use pretty_assertions::assert_eq;
+
+#[test]
+// Test that every fixture is the same after XML -> struct -> JSON -> struct
+fn fixtures_round_trip() {
+    insta::glob!("fixtures/*.xml", |path| {
+        let xml = std::fs::read_to_string(path).unwrap();
+        let expected = from_str_with_options(&xml, &ParseOptions { lossless: true }).unwrap();
+        for shape in [DetailShape::Object, DetailShape::Array] {
+            let json = expected.to_json_value(&shape).unwrap().to_string();
+            let actual = from_json_str(&json).unwrap();
+            assert_eq!(expected, actual, "{}", path.display());
+        }
+    })
+}
+
+#[test]
+fn testsuite_to_xml() {
+    let json = r#"{
+  "testsuite": {
+    "name": "suite1",
+    "tests": 2,
+    "time": 0.1,
+    "testcase": [
+      {
+        "name": "case1",
+        "failure": { "message": "failure1", "type": "AssertionError", "inner": "stack & trace" }
+      },
+      {
+        "name": "case2",
+        "skipped": [{ "message": "skipped1" }],
+        "system-out": ["system out text"],
+        "extra": { "@retries": "2" }
+      }
+    ]
+  }
+}"#;
+    let testsuite = from_json_str(json).unwrap();
+    let expected = r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuite name="suite1" tests="2" time="0.1">
+  <testcase name="case1">
+    <failure message="failure1" type="AssertionError">stack &amp; trace</failure>
+  </testcase>
+  <testcase name="case2" retries="2">
+    <skipped message="skipped1"/>
+    <system-out>system out text</system-out>
+  </testcase>
+</testsuite>
+"#;
+    assert_eq!(to_string(&testsuite).unwrap(), expected);
+}
+
+#[test]
+fn unexpected_root() {
+    assert!(from_json_str(r#"{"testcase": {"name": "case1"}}"#).is_err());
+    assert!(from_json_str(r#"[]"#).is_err());
+}
+
+#[test]
+// Test that the CLI output of many files with `path` and `metadata` is converted back to the same XML
+fn cli_output_with_path_and_metadata_to_xml() {
+    let fixtures = [
+        "tests/fixtures/jest-failure.xml",
+        "tests/fixtures/cargo-nextest.xml",
+    ];
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_junit2json"))
+        .args(["--output", "ndjson", "--detail-shape", "array"])
+        .args(["--meta", "commit=abc123"])
+        .args(fixtures)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    let lines = String::from_utf8(output.stdout).unwrap();
+    for (fixture, line) in fixtures.iter().zip(lines.lines()) {
+        let value: serde_json::Value = serde_json::from_str(line).unwrap();
+        assert!(value["path"].is_string());
+        assert!(value["metadata"].is_object());
+        let mut child = std::process::Command::new(env!("CARGO_BIN_EXE_junit2json"))
+            .arg("to-xml")
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .spawn()
+            .unwrap();
+        std::io::Write::write_all(&mut child.stdin.take().unwrap(), line.as_bytes()).unwrap();
+        let output = child.wait_with_output().unwrap();
+        assert!(output.status.success(), "{:?}", output);
+        let expected = from_str(&std::fs::read_to_string(fixture).unwrap()).unwrap();
+        let actual = from_str(&String::from_utf8(output.stdout).unwrap()).unwrap();
+        assert_eq!(expected, actual, "{}", fixture);
+    }
+}
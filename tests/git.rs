@@ -0,0 +1,166 @@
+use junit2json::*;
+use pretty_assertions::assert_eq;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+fn git(dir: &Path, args: &[&str]) -> String {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .env("GIT_CONFIG_NOSYSTEM", "1")
+        .env("GIT_CONFIG_GLOBAL", "/dev/null")
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "git {:?}: {:?}", args, output);
+    String::from_utf8(output.stdout).unwrap().trim().to_string()
+}
+
+/// Create a repository with commits whose messages are similar, so that they are deltified in a pack.
+fn repository(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("junit2json-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("src")).unwrap();
+    git(&dir, &["init", "-q", "-b", "main"]);
+    git(&dir, &["config", "user.name", "Test User"]);
+    git(&dir, &["config", "user.email", "test@example.com"]);
+    let message = "A long commit message that is repeated in every commit. ".repeat(20);
+    for index in 0..5 {
+        fs::write(dir.join("src/lib.rs"), format!("// {}\n", index)).unwrap();
+        git(&dir, &["add", "-A"]);
+        let date = format!("2024-01-0{}T03:04:05+09:00", index + 1);
+        let status = Command::new("git")
+            .args(["commit", "-q", "-m", &format!("{}{}", message, index)])
+            .env("GIT_COMMITTER_DATE", &date)
+            .env("GIT_AUTHOR_DATE", &date)
+            .current_dir(&dir)
+            .status()
+            .unwrap();
+        assert!(status.success(), "git commit: {:?}", status);
+    }
+    dir
+}
+
+fn expected(dir: &Path, commit: &str) -> (String, String) {
+    let author = git(dir, &["log", "-1", "--format=%an <%ae>", commit]);
+    let time = git(dir, &["log", "-1", "--format=%cI", commit]);
+    (author, time)
+}
+
+#[test]
+// Test that HEAD of loose objects and packed objects with deltas is read like git log
+fn discover_loose_and_packed() {
+    let dir = repository("packed");
+    let git_info = GitInfo::discover(&dir.join("src")).unwrap();
+    let head = git(&dir, &["rev-parse", "HEAD"]);
+    assert_eq!(git_info.root, dir.canonicalize().unwrap());
+    assert_eq!(git_info.commit, Some(head.clone()));
+    assert_eq!(git_info.branch, Some("main".to_string()));
+    assert_eq!(
+        git_info.author,
+        Some("Test User <test@example.com>".to_string())
+    );
+    assert_eq!(
+        git_info.commit_time,
+        Some("2024-01-05T03:04:05+09:00".to_string())
+    );
+
+    git(&dir, &["gc", "-q", "--aggressive"]);
+    git(
+        &dir,
+        &[
+            "repack",
+            "-a",
+            "-d",
+            "-f",
+            "-q",
+            "--window=50",
+            "--depth=50",
+        ],
+    );
+    let commits = git(&dir, &["rev-list", "HEAD"]);
+    for commit in commits.lines() {
+        // Detach HEAD at each commit
+        fs::write(dir.join(".git/HEAD"), format!("{}\n", commit)).unwrap();
+        let git_info = GitInfo::discover(&dir).unwrap();
+        let (author, time) = expected(&dir, commit);
+        assert_eq!(git_info.commit.as_deref(), Some(commit));
+        assert_eq!(git_info.branch, None);
+        assert_eq!(git_info.author, Some(author));
+        assert_eq!(git_info.commit_time, Some(time));
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+// Test that a worktree reads HEAD of its own and objects of the main repository
+fn discover_worktree() {
+    let dir = repository("worktree");
+    let worktree = dir.with_extension("worktree");
+    let _ = fs::remove_dir_all(&worktree);
+    git(&dir, &["gc", "-q"]);
+    git(
+        &dir,
+        &[
+            "worktree",
+            "add",
+            "-q",
+            "-b",
+            "feature",
+            worktree.to_str().unwrap(),
+            "HEAD~2",
+        ],
+    );
+    let git_info = GitInfo::discover(&worktree).unwrap();
+    assert_eq!(git_info.branch, Some("feature".to_string()));
+    assert_eq!(git_info.commit, Some(git(&dir, &["rev-parse", "HEAD~2"])));
+    assert_eq!(
+        git_info.commit_time,
+        Some("2024-01-03T03:04:05+09:00".to_string())
+    );
+    fs::remove_dir_all(&worktree).unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+// Test that file of testcases is resolved to the path relative to the root
+fn relativize_files() {
+    let dir = repository("relative");
+    let git_info = GitInfo::discover(&dir.join("src")).unwrap();
+    let absolute = dir.join("src/lib.rs");
+    let xml = format!(
+        r#"
+      <testsuite name="suite1" file="lib.rs">
+          <testcase name="case1" file="{}" />
+          <testcase name="case2" file="../src/./lib.rs" />
+          <testcase name="case3" file="/usr/lib/other.rs" />
+      </testsuite>
+    "#,
+        absolute.display()
+    );
+    let mut testsuite = from_str(&xml).unwrap();
+    testsuite.relativize_files(&git_info);
+    let TestSuitesOrTestSuite::TestSuite(testsuite) = testsuite else {
+        panic!("not testsuite");
+    };
+    assert_eq!(testsuite.file, Some("src/lib.rs".to_string()));
+    let files = testsuite
+        .testcase
+        .unwrap()
+        .into_iter()
+        .map(|testcase| testcase.file.unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(files, ["src/lib.rs", "src/lib.rs", "/usr/lib/other.rs"]);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn not_repository() {
+    let dir = std::env::temp_dir().join(format!("junit2json-none-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let result = GitInfo::discover(&dir);
+    fs::remove_dir_all(&dir).unwrap();
+    if let Err(err) = result {
+        assert!(err.to_string().starts_with("not a git repository"));
+    }
+}
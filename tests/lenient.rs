@@ -0,0 +1,154 @@
+use junit2json::*;
+use pretty_assertions::assert_eq;
+
+#[test]
+/// Test when numeric attributes are not a number
+fn invalid_numbers_become_none() {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites tests="N/A">
+    <testsuite name="suite1" time="0.1">
+        <testcase name="case1" time="N/A" line="12" />
+    </testsuite>
+</testsuites>
+"#;
+    let (actual, warnings) = from_str_lenient(xml, &ParseOptions::default()).unwrap();
+    assert_eq!(
+        actual,
+        TestSuitesOrTestSuite::TestSuites(TestSuites {
+            testsuite: Some(vec![TestSuite {
+                name: Some("suite1".to_string()),
+                time: Some(0.1),
+                testcase: Some(vec![TestCase {
+                    name: Some("case1".to_string()),
+                    line: Some(12),
+                    ..Default::default()
+                }]),
+                ..Default::default()
+            }]),
+            ..Default::default()
+        })
+    );
+    let paths = warnings
+        .iter()
+        .map(|warning| warning.location.path.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        paths,
+        vec![
+            "testsuites/@tests",
+            "testsuites/testsuite[1]/testcase[1]/@time"
+        ]
+    );
+}
+
+#[test]
+/// Test when XML is truncated in the middle of testcase
+fn truncated_xml() {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites>
+    <testsuite name="suite1">
+        <testcase name="case1" />
+    </testsuite>
+    <testsuite name="suite2">
+        <testcase name="case2">
+            <failure message="failure1">failure text</failure>
+        </testcase>
+        <testcase name="case3">
+            <system-out>system out te"#;
+    let (actual, warnings) = from_str_lenient(xml, &ParseOptions::default()).unwrap();
+    assert_eq!(
+        actual,
+        TestSuitesOrTestSuite::TestSuites(TestSuites {
+            testsuite: Some(vec![
+                TestSuite {
+                    name: Some("suite1".to_string()),
+                    testcase: Some(vec![TestCase {
+                        name: Some("case1".to_string()),
+                        ..Default::default()
+                    }]),
+                    ..Default::default()
+                },
+                TestSuite {
+                    name: Some("suite2".to_string()),
+                    testcase: Some(vec![TestCase {
+                        name: Some("case2".to_string()),
+                        failure: Some(vec![Detail {
+                            message: Some("failure1".to_string()),
+                            inner: Some("failure text".to_string()),
+                            ..Default::default()
+                        }]),
+                        ..Default::default()
+                    }]),
+                    ..Default::default()
+                }
+            ]),
+            ..Default::default()
+        })
+    );
+    assert_eq!(warnings.len(), 2);
+    assert_eq!(
+        warnings[1].location.path,
+        "testsuites/testsuite[2]/testcase[2]"
+    );
+    assert_eq!(warnings[1].location.line, 10);
+}
+
+#[test]
+/// Test when XML has mismatched end tag
+fn syntax_error() {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuite name="suite1">
+    <testcase name="case1" />
+    <testcase name="case2">
+</testsuite>
+"#;
+    let (actual, warnings) = from_str_lenient(xml, &ParseOptions::default()).unwrap();
+    assert_eq!(
+        actual,
+        TestSuitesOrTestSuite::TestSuite(Box::new(TestSuite {
+            name: Some("suite1".to_string()),
+            testcase: Some(vec![TestCase {
+                name: Some("case1".to_string()),
+                ..Default::default()
+            }]),
+            ..Default::default()
+        }))
+    );
+    assert_eq!(warnings.len(), 2);
+}
+
+#[test]
+/// Test when XML is valid
+fn valid_xml() {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites name="suites1">
+    <testsuite name="suite1" time="0.1">
+        <testcase name="case1"><![CDATA[<cdata>]]></testcase>
+        <testcase name="case2"><system-out>&lt;escaped&gt;</system-out></testcase>
+    </testsuite>
+</testsuites>
+"#;
+    let (actual, warnings) = from_str_lenient(xml, &ParseOptions::default()).unwrap();
+    assert_eq!(actual, from_str(xml).unwrap());
+    assert!(warnings.is_empty());
+}
+
+#[test]
+/// Test that every numeric attribute is checked, so that the lenient mode never fails on them
+fn every_numeric_attribute_is_checked() {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites time="x" tests="x" failures="x" errors="x" skipped="x" assertions="x">
+    <testsuite tests="x" failures="x" errors="x" time="x" disabled="x" skipped="x">
+        <testcase assertions="x" time="x" line="x">
+            <flakyFailure time="x" />
+        </testcase>
+    </testsuite>
+</testsuites>
+"#;
+    let (_, warnings) = from_str_lenient(xml, &ParseOptions::default()).unwrap();
+    assert_eq!(warnings.len(), 16);
+    assert_eq!(
+        warnings[0].message,
+        r#"ignored invalid value "x" (expected f32: invalid float literal)"#
+    );
+}
@@ -0,0 +1,100 @@
+mod common;
+
+use common::unknown_items_fixture;
+use junit2json::*;
+use pretty_assertions::assert_eq;
+use serde_json::json;
+
+#[test]
+/// Test when lossless is false
+fn lossless_disabled() {
+    let xml = unknown_items_fixture();
+    let actual = from_str_with_options(xml, &ParseOptions::default()).unwrap();
+
+    assert_eq!(actual, from_str(xml).unwrap());
+}
+
+#[test]
+/// Test when lossless is true
+fn lossless_enabled() {
+    let xml = unknown_items_fixture();
+    let options = ParseOptions { lossless: true };
+    let actual = from_str_with_options(xml, &options).unwrap();
+
+    let expect = json!({
+        "testsuites": {
+            "name": "suites1",
+            "testsuite": [
+                {
+                    "name": "suite1",
+                    "testcase": [
+                        {
+                            "name": "case1",
+                            "failure": [
+                                {
+                                    "message": "failure1",
+                                    "inner": "failure text",
+                                    "extra": {
+                                        "@attempt": "1"
+                                    }
+                                }
+                            ],
+                            "extra": {
+                                "@retries": "2",
+                                "custom-tag": [
+                                    "custom text",
+                                    {
+                                        "@key": "value",
+                                        "nested": ["nested text"]
+                                    }
+                                ]
+                            }
+                        }
+                    ],
+                    "extra": {
+                        "@custom": "foo"
+                    }
+                }
+            ],
+            "extra": {
+                "@uuid": "d241b926"
+            }
+        }
+    });
+    assert_eq!(serde_json::to_value(actual).unwrap(), expect);
+}
+
+#[test]
+/// Test when lossless is true and root is testsuite with nested testsuite
+fn lossless_nested_testsuite() {
+    let xml = r#"
+      <?xml version="1.0" encoding="UTF-8"?>
+      <testsuite name="parent" custom="foo">
+          <testsuite name="child" custom="bar">
+              <testcase name="case1" custom="baz" />
+          </testsuite>
+      </testsuite>
+  "#;
+    let options = ParseOptions { lossless: true };
+    let actual = from_str_with_options(xml, &options).unwrap();
+
+    let expect = json!({
+        "testsuite": {
+            "name": "parent",
+            "testsuite": [
+                {
+                    "name": "child",
+                    "testcase": [
+                        {
+                            "name": "case1",
+                            "extra": { "@custom": "baz" }
+                        }
+                    ],
+                    "extra": { "@custom": "bar" }
+                }
+            ],
+            "extra": { "@custom": "foo" }
+        }
+    });
+    assert_eq!(serde_json::to_value(actual).unwrap(), expect);
+}
@@ -0,0 +1,148 @@
+use junit2json::*;
+use pretty_assertions::assert_eq;
+
+#[test]
+// Test that bare <testsuite> roots are wrapped and counters are summed
+fn wrap_testsuite_roots() {
+    let shard1 = from_str(
+        r#"
+          <testsuite name="suite1" tests="2" failures="1" time="0.1" timestamp="2024-03-02T10:11:13">
+              <testcase name="case1" />
+              <testcase name="case2"><failure message="failed" /></testcase>
+          </testsuite>
+      "#,
+    )
+    .unwrap();
+    let shard2 = from_str(
+        r#"
+          <testsuites tests="1" skipped="1" time="0.2" timestamp="2024-03-02T10:11:12">
+              <testsuite name="suite2" tests="1" skipped="1" time="0.2">
+                  <testcase name="case3"><skipped /></testcase>
+              </testsuite>
+          </testsuites>
+      "#,
+    )
+    .unwrap();
+    let options = MergeOptions {
+        name: Some("merged".to_string()),
+        ..Default::default()
+    };
+    let merged = TestSuitesOrTestSuite::merge(vec![shard1, shard2], &options);
+    assert_eq!(merged.name, Some("merged".to_string()));
+    assert_eq!(merged.tests, Some(3));
+    assert_eq!(merged.failures, Some(1));
+    assert_eq!(merged.skipped, Some(1));
+    assert_eq!(merged.errors, None);
+    assert_eq!(merged.time, Some(0.3));
+    assert_eq!(merged.timestamp, Some("2024-03-02T10:11:12".to_string()));
+    let names = merged
+        .testsuite
+        .unwrap()
+        .into_iter()
+        .map(|testsuite| testsuite.name.unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["suite1", "suite2"]);
+}
+
+#[test]
+// Test that testsuites with the same name are merged only with the option
+fn merge_same_name() {
+    let shard = |case: &str| {
+        from_str(&format!(
+            r#"
+              <testsuite name="suite1" tests="1" time="0.5" hostname="runner-{case}">
+                  <properties><property name="os" value="linux" /></properties>
+                  <testcase name="{case}" />
+              </testsuite>
+          "#
+        ))
+        .unwrap()
+    };
+    let merged = TestSuitesOrTestSuite::merge(
+        vec![shard("case1"), shard("case2")],
+        &MergeOptions::default(),
+    );
+    assert_eq!(merged.testsuite.unwrap().len(), 2);
+
+    let options = MergeOptions {
+        merge_same_name: true,
+        ..Default::default()
+    };
+    let merged = TestSuitesOrTestSuite::merge(vec![shard("case1"), shard("case2")], &options);
+    assert_eq!(merged.tests, Some(2));
+    let testsuite = &merged.testsuite.unwrap()[0];
+    assert_eq!(testsuite.tests, Some(2));
+    assert_eq!(testsuite.time, Some(1.0));
+    assert_eq!(testsuite.hostname, Some("runner-case1".to_string()));
+    assert_eq!(
+        testsuite
+            .properties
+            .as_ref()
+            .unwrap()
+            .property
+            .as_ref()
+            .unwrap()
+            .len(),
+        1
+    );
+    let names = testsuite
+        .testcase
+        .iter()
+        .flatten()
+        .map(|testcase| testcase.name.clone().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["case1", "case2"]);
+}
+
+#[test]
+// Test that counters of <testsuites> are summed from its testsuite when it does not have them
+fn testsuites_without_counters() {
+    let report = from_str(
+        r#"
+          <testsuites>
+              <testsuite name="suite1" tests="2" failures="1" />
+              <testsuite name="suite2" tests="3" errors="1" />
+          </testsuites>
+      "#,
+    )
+    .unwrap();
+    let merged = TestSuitesOrTestSuite::merge(vec![report], &MergeOptions::default());
+    assert_eq!(merged.tests, Some(5));
+    assert_eq!(merged.failures, Some(1));
+    assert_eq!(merged.errors, Some(1));
+    assert_eq!(merged.time, None);
+}
+
+#[test]
+// Test that shards which partially declare their counters are recounted from their testcases
+fn testsuites_with_partial_counters() {
+    let shard1 = from_str(
+        r#"
+          <testsuite name="suite1" tests="1">
+              <testcase name="case1" />
+          </testsuite>
+      "#,
+    )
+    .unwrap();
+    let shard2 = from_str(
+        r#"
+          <testsuites>
+              <testsuite name="suite1">
+                  <testcase name="case2" />
+                  <testcase name="case3"><failure message="failed" /></testcase>
+              </testsuite>
+          </testsuites>
+      "#,
+    )
+    .unwrap();
+    let options = MergeOptions {
+        merge_same_name: true,
+        ..Default::default()
+    };
+    let merged = TestSuitesOrTestSuite::merge(vec![shard1, shard2], &options);
+    assert_eq!(merged.tests, Some(3));
+    assert_eq!(merged.failures, Some(1));
+    let testsuite = &merged.testsuite.unwrap()[0];
+    assert_eq!(testsuite.tests, Some(3));
+    assert_eq!(testsuite.failures, Some(1));
+}
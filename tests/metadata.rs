@@ -0,0 +1,141 @@
+use clap::Parser;
+use junit2json::cli::Args;
+use junit2json::*;
+use pretty_assertions::assert_eq;
+use serde_json::json;
+use std::collections::HashMap;
+
+fn detect(env: &[(&str, &str)]) -> serde_json::Value {
+    let env = HashMap::<_, _>::from_iter(env.iter().cloned());
+    serde_json::Value::Object(detect_ci_metadata(|name| {
+        env.get(name).map(|value| value.to_string())
+    }))
+}
+
+#[test]
+// Test that variables of each CI are mapped to the same keys
+fn detect_each_ci() {
+    let actual = detect(&[
+        ("GITHUB_ACTIONS", "true"),
+        ("GITHUB_REPOSITORY", "owner/repo"),
+        ("GITHUB_SHA", "0123abc"),
+        ("GITHUB_REF", "refs/heads/main"),
+        ("GITHUB_REF_NAME", "main"),
+        ("GITHUB_REF_TYPE", "branch"),
+        ("GITHUB_HEAD_REF", ""),
+        ("GITHUB_WORKFLOW", "CI"),
+        ("GITHUB_SERVER_URL", "https://github.com"),
+        ("GITHUB_RUN_ID", "42"),
+        ("GITHUB_JOB", "test"),
+    ]);
+    assert_eq!(
+        actual,
+        json!({
+            "ci": "github-actions",
+            "repository": "owner/repo",
+            "commit": "0123abc",
+            "branch": "main",
+            "workflow": "CI",
+            "pipeline": "42",
+            "pipeline_url": "https://github.com/owner/repo/actions/runs/42",
+            "job": "test",
+        })
+    );
+
+    let actual = detect(&[
+        ("GITHUB_ACTIONS", "true"),
+        ("GITHUB_REF", "refs/pull/7/merge"),
+        ("GITHUB_REF_NAME", "7/merge"),
+        ("GITHUB_HEAD_REF", "feature"),
+    ]);
+    assert_eq!(
+        actual,
+        json!({"ci": "github-actions", "branch": "feature", "pull_request": "7"})
+    );
+
+    let actual = detect(&[
+        ("CIRCLECI", "true"),
+        ("CIRCLE_PROJECT_USERNAME", "owner"),
+        ("CIRCLE_PROJECT_REPONAME", "repo"),
+        (
+            "CIRCLE_PULL_REQUEST",
+            "https://github.com/owner/repo/pull/7",
+        ),
+        ("CIRCLE_JOB", "test"),
+    ]);
+    assert_eq!(
+        actual,
+        json!({"ci": "circleci", "repository": "owner/repo", "pull_request": "7", "job": "test"})
+    );
+
+    let actual = detect(&[
+        ("JENKINS_URL", "https://jenkins.example.com/"),
+        ("GIT_COMMIT", "0123abc"),
+        ("BUILD_NUMBER", "42"),
+    ]);
+    assert_eq!(
+        actual,
+        json!({"ci": "jenkins", "commit": "0123abc", "pipeline": "42"})
+    );
+
+    let actual = detect(&[
+        ("BUILDKITE", "true"),
+        ("BUILDKITE_PULL_REQUEST", "false"),
+        (
+            "BUILDKITE_BUILD_URL",
+            "https://buildkite.com/org/pipeline/builds/42",
+        ),
+        ("BUILDKITE_JOB_ID", "job-1"),
+    ]);
+    assert_eq!(
+        actual,
+        json!({
+            "ci": "buildkite",
+            "pipeline_url": "https://buildkite.com/org/pipeline/builds/42",
+            "job_url": "https://buildkite.com/org/pipeline/builds/42#job-1",
+        })
+    );
+
+    assert_eq!(detect(&[("CI", "true")]), json!({}));
+}
+
+#[test]
+// Test that --meta overrides the detected metadata and is added after the root
+fn meta_args() {
+    let args = Args::parse_from(["junit2json", "--meta", "env=prod", "--meta", "url=a=b"]);
+    let metadata = args.metadata(None);
+    assert_eq!(
+        serde_json::Value::Object(metadata.clone()),
+        json!({"env": "prod", "url": "a=b"})
+    );
+    assert!(Args::try_parse_from(["junit2json", "--meta", "=prod"]).is_err());
+
+    let mut value = json!({"testsuite": {"name": "suite1"}});
+    add_metadata(&mut value, &metadata);
+    assert_eq!(
+        value,
+        json!({"testsuite": {"name": "suite1"}, "metadata": {"env": "prod", "url": "a=b"}})
+    );
+    let mut value = json!({"testsuite": {}});
+    add_metadata(&mut value, &serde_json::Map::new());
+    assert_eq!(value, json!({"testsuite": {}}));
+}
+
+#[test]
+// Test that the output with metadata is valid against the JSON Schema with metadata
+fn json_schema_metadata() {
+    let schema = json_schema_with_metadata(json_schema(&cli::DetailShape::Object));
+    let validator = jsonschema::draft202012::new(&schema).unwrap();
+    let mut value = from_str(r#"<testsuite name="suite1"><testcase name="case1" /></testsuite>"#)
+        .unwrap()
+        .to_json_value(&cli::DetailShape::Object)
+        .unwrap();
+    let metadata = json!({"commit": "0123abc"});
+    add_metadata(&mut value, metadata.as_object().unwrap());
+    assert!(validator.is_valid(&value));
+    assert!(
+        !jsonschema::draft202012::new(&json_schema(&cli::DetailShape::Object))
+            .unwrap()
+            .is_valid(&value)
+    );
+}
@@ -0,0 +1,96 @@
+use junit2json::cli::DetailShape;
+use junit2json::*;
+use pretty_assertions::assert_eq;
+
+fn outcomes(xml: &str) -> Vec<Outcome> {
+    let TestSuitesOrTestSuite::TestSuite(testsuite) = from_str(xml).unwrap() else {
+        panic!("not testsuite");
+    };
+    testsuite
+        .testcase
+        .unwrap()
+        .iter()
+        .map(|testcase| testcase.outcome())
+        .collect()
+}
+
+#[test]
+// Test the outcome derived from the children
+fn outcome_from_children() {
+    let xml = r#"
+      <testsuite name="suite1">
+          <testcase name="passed" />
+          <testcase name="failed"><failure /></testcase>
+          <testcase name="errored"><error /></testcase>
+          <testcase name="skipped"><skipped /></testcase>
+          <testcase name="flaky"><flakyFailure /></testcase>
+          <testcase name="rerun_failed"><failure /><rerunFailure /></testcase>
+          <testcase name="failed_and_errored"><failure /><error /></testcase>
+      </testsuite>
+    "#;
+    assert_eq!(
+        outcomes(xml),
+        vec![
+            Outcome::Passed,
+            Outcome::Failed,
+            Outcome::Errored,
+            Outcome::Skipped,
+            Outcome::Flaky,
+            Outcome::Failed,
+            Outcome::Failed,
+        ]
+    );
+}
+
+#[test]
+// Test the outcome derived from the status attribute
+fn outcome_from_status() {
+    let xml = r#"
+      <testsuite name="suite1">
+          <testcase name="run" status="run" />
+          <testcase name="notrun" status="notrun" />
+          <testcase name="disabled_skipped" status="disabled"><skipped /></testcase>
+          <testcase name="failed" status="FAILED" />
+          <testcase name="error" status="error" />
+          <testcase name="ignored" status="ignored" />
+          <testcase name="failure_wins" status="disabled"><failure /></testcase>
+      </testsuite>
+    "#;
+    assert_eq!(
+        outcomes(xml),
+        vec![
+            Outcome::Passed,
+            Outcome::Disabled,
+            Outcome::Disabled,
+            Outcome::Failed,
+            Outcome::Errored,
+            Outcome::Skipped,
+            Outcome::Failed,
+        ]
+    );
+}
+
+#[test]
+// Test that outcome is output only after set_outcomes, and is not read back from JSON
+fn outcome_in_json() {
+    let xml = r#"
+      <testsuites>
+          <testsuite name="suite1">
+              <testsuite name="nested">
+                  <testcase name="case1"><failure /></testcase>
+              </testsuite>
+          </testsuite>
+      </testsuites>
+    "#;
+    let mut testsuites = from_str(xml).unwrap();
+    let json = testsuites.to_json_value(&DetailShape::Object).unwrap();
+    assert!(json.to_string().find("outcome").is_none());
+
+    testsuites.set_outcomes();
+    let json = testsuites.to_json_value(&DetailShape::Object).unwrap();
+    assert_eq!(
+        json["testsuites"]["testsuite"][0]["testsuite"][0]["testcase"][0]["outcome"],
+        "failed"
+    );
+    assert_eq!(from_json_value(json).unwrap(), from_str(xml).unwrap());
+}
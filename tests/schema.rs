@@ -0,0 +1,195 @@
+use junit2json::cli::DetailShape;
+use junit2json::*;
+use pretty_assertions::assert_eq;
+use serde_json::Value;
+use std::fs::File;
+use std::io::BufReader;
+
+/// Assert that the JSON object can be loaded into a table of the BigQuery schema.
+fn assert_loadable(fields: &[BigQueryField], object: &Value, path: &str) {
+    for (key, value) in object.as_object().unwrap() {
+        let path = format!("{}.{}", path, key);
+        let field = fields
+            .iter()
+            .find(|field| &field.name == key)
+            .unwrap_or_else(|| panic!("{} is not in schema", path));
+        let values = match field.mode.as_str() {
+            "REPEATED" => value
+                .as_array()
+                .unwrap_or_else(|| panic!("{} is not array", path))
+                .iter()
+                .collect::<Vec<_>>(),
+            _ => vec![value],
+        };
+        for value in values {
+            match field.r#type.as_str() {
+                "RECORD" => assert_loadable(field.fields.as_ref().unwrap(), value, &path),
+                "STRING" => assert!(value.is_string(), "{} is not string", path),
+                "INTEGER" => assert!(value.is_u64(), "{} is not integer", path),
+                "FLOAT" => assert!(value.is_number(), "{} is not number", path),
+                "JSON" => {}
+                other => panic!("unexpected type {} of {}", other, path),
+            }
+        }
+    }
+}
+
+#[test]
+fn field_modes() {
+    let schema = bigquery_schema(&DetailShape::Object);
+    let testsuites = schema.iter().find(|f| f.name == "testsuites").unwrap();
+    let testsuite = testsuites
+        .fields
+        .as_ref()
+        .unwrap()
+        .iter()
+        .find(|f| f.name == "testsuite")
+        .unwrap();
+    assert_eq!(testsuite.r#type, "RECORD");
+    assert_eq!(testsuite.mode, "REPEATED");
+
+    let field = |name: &str| {
+        testsuite
+            .fields
+            .as_ref()
+            .unwrap()
+            .iter()
+            .find(|f| f.name == name)
+            .unwrap()
+            .clone()
+    };
+    assert_eq!(field("system-out").mode, "REPEATED");
+    assert_eq!(field("system-out").r#type, "STRING");
+    assert_eq!(field("testcase").mode, "REPEATED");
+    assert_eq!(field("time").r#type, "FLOAT");
+    assert_eq!(field("tests").r#type, "INTEGER");
+    // BigQuery does not support recursive records
+    assert_eq!(field("testsuite").r#type, "JSON");
+    let property = &field("properties").fields.unwrap()[0];
+    assert_eq!(property.name, "property");
+    assert_eq!(property.mode, "REPEATED");
+
+    let failure = |shape: &DetailShape| {
+        bigquery_flat_schema(shape)
+            .into_iter()
+            .find(|f| f.name == "failure")
+            .unwrap()
+    };
+    assert_eq!(failure(&DetailShape::Object).mode, "NULLABLE");
+    assert_eq!(failure(&DetailShape::Array).mode, "REPEATED");
+    assert_eq!(failure(&DetailShape::Array).r#type, "RECORD");
+}
+
+#[test]
+// Test that the output of all fixtures can be loaded with the schema
+fn fixtures_are_loadable() {
+    for shape in [DetailShape::Object, DetailShape::Array] {
+        let schema = bigquery_schema(&shape);
+        let flat_schema = bigquery_flat_schema(&shape);
+        for entry in std::fs::read_dir("tests/fixtures").unwrap() {
+            let path = entry.unwrap().path();
+            let file = File::open(&path).unwrap();
+            let options = ParseOptions { lossless: true };
+            let mut testsuites = from_reader_with_options(BufReader::new(file), &options).unwrap();
+            testsuites.set_outcomes();
+            let value = testsuites.to_json_value(&shape).unwrap();
+            assert_loadable(&schema, &value, &path.display().to_string());
+
+            for item in stream_from_reader(BufReader::new(File::open(&path).unwrap())) {
+                let (context, mut testcase) = item.unwrap();
+                testcase.outcome = Some(testcase.outcome());
+                let value = context.flatten(testcase).to_json_value(&shape).unwrap();
+                assert_loadable(&flat_schema, &value, &path.display().to_string());
+            }
+        }
+    }
+}
+
+#[test]
+// Test that every fixture snapshot is valid against the JSON Schema
+fn snapshots_are_valid_json_schema() {
+    let validator = jsonschema::draft202012::new(&json_schema(&DetailShape::Object)).unwrap();
+    for entry in std::fs::read_dir("tests/snapshots").unwrap() {
+        let path = entry.unwrap().path();
+        let snapshot = std::fs::read_to_string(&path).unwrap();
+        // Skip the metadata header of insta
+        let (_, json) = snapshot.split_once("\n---\n").unwrap();
+        let value: Value = serde_json::from_str(json).unwrap();
+        let errors = validator
+            .iter_errors(&value)
+            .map(|err| format!("{} at {}", err, err.instance_path()))
+            .collect::<Vec<_>>();
+        assert_eq!(errors, Vec::<String>::new(), "{}", path.display());
+    }
+}
+
+#[test]
+fn json_schema_detail_shape() {
+    let fixture = "tests/fixtures/jest-failure.xml";
+    let array = from_reader(BufReader::new(File::open(fixture).unwrap()))
+        .unwrap()
+        .to_json_value(&DetailShape::Array)
+        .unwrap();
+    assert!(jsonschema::draft202012::is_valid(
+        &json_schema(&DetailShape::Array),
+        &array
+    ));
+    assert!(!jsonschema::draft202012::is_valid(
+        &json_schema(&DetailShape::Object),
+        &array
+    ));
+
+    let flat_schema = json_flat_schema(&DetailShape::Object);
+    for item in stream_from_reader(BufReader::new(File::open(fixture).unwrap())) {
+        let (context, testcase) = item.unwrap();
+        let value = context
+            .flatten(testcase)
+            .to_json_value(&DetailShape::Object)
+            .unwrap();
+        assert!(jsonschema::draft202012::is_valid(&flat_schema, &value));
+    }
+}
+
+#[test]
+// Test that the CLI output of many files with `path` is valid against the schemas
+fn cli_output_with_path_is_valid() {
+    let run = |args: &[&str]| {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_junit2json"))
+            .args(args)
+            .args([
+                "tests/fixtures/jest-failure.xml",
+                "tests/fixtures/cargo-nextest.xml",
+            ])
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{:?}", output);
+        String::from_utf8(output.stdout)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str::<Value>(line).unwrap())
+            .collect::<Vec<_>>()
+    };
+    for (args, schema, bigquery) in [
+        (
+            ["--output", "ndjson"].as_slice(),
+            json_schema(&DetailShape::Object),
+            bigquery_schema(&DetailShape::Object),
+        ),
+        (
+            ["--flat"].as_slice(),
+            json_flat_schema(&DetailShape::Object),
+            bigquery_flat_schema(&DetailShape::Object),
+        ),
+    ] {
+        let validator = jsonschema::draft202012::new(&schema).unwrap();
+        for value in run(args) {
+            assert!(value["path"].is_string());
+            let errors = validator
+                .iter_errors(&value)
+                .map(|err| format!("{} at {}", err, err.instance_path()))
+                .collect::<Vec<_>>();
+            assert_eq!(errors, Vec::<String>::new(), "{:?}", args);
+            assert_loadable(&bigquery, &value, &format!("{:?}", args));
+        }
+    }
+}
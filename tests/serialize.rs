@@ -1,3 +1,4 @@
+use junit2json::cli::DetailShape;
 use pretty_assertions::assert_eq;
 use serde_json::json;
 
@@ -58,7 +59,7 @@ fn testcase_failure_has_inner() {
     });
 
     let actual = junit2json::from_str(xml).unwrap();
-    let actual_json_value = serde_json::to_value(actual).unwrap();
+    let actual_json_value = actual.to_json_value(&DetailShape::Object).unwrap();
     assert_eq!(actual_json_value, expect);
 }
 
@@ -94,7 +95,7 @@ fn skipped_testcase() {
     });
 
     let actual = junit2json::from_str(xml).unwrap();
-    let actual_json_value = serde_json::to_value(actual).unwrap();
+    let actual_json_value = actual.to_json_value(&DetailShape::Object).unwrap();
     assert_eq!(actual_json_value, expect);
 }
 
@@ -257,3 +258,117 @@ fn some_testsuite_property_are_empty() {
     let actual_json_value = serde_json::to_value(actual).unwrap();
     assert_eq!(actual_json_value, expect);
 }
+
+#[test]
+// Test when testsuite has nested testsuite
+fn nested_testsuite() {
+    let xml = r#"
+        <?xml version="1.0" encoding="UTF-8"?>
+        <testsuite name="parent">
+            <testsuite name="child">
+                <testcase name="case1"/>
+            </testsuite>
+        </testsuite>
+    "#;
+    let expect = json!({
+        "testsuite": {
+            "name": "parent",
+            "testsuite": [
+                {
+                    "name": "child",
+                    "testcase": [
+                        {
+                            "name": "case1"
+                        }
+                    ]
+                }
+            ]
+        }
+    });
+
+    let actual = junit2json::from_str(xml).unwrap();
+    let actual_json_value = serde_json::to_value(actual).unwrap();
+    assert_eq!(actual_json_value, expect);
+}
+
+#[test]
+// Test when testcase has multiple failures with object shape
+fn multiple_failures_object_shape() {
+    let xml = r#"
+        <?xml version="1.0" encoding="UTF-8"?>
+        <testsuite>
+            <testcase>
+                <failure message="failure1" />
+                <failure message="failure2" />
+            </testcase>
+        </testsuite>
+    "#;
+    let expect = json!({
+        "testsuite": {
+            "testcase": [
+                {
+                    "failure": {
+                        "message": "failure1"
+                    }
+                }
+            ]
+        }
+    });
+
+    let actual = junit2json::from_str(xml).unwrap();
+    let actual_json_value = actual.to_json_value(&DetailShape::Object).unwrap();
+    assert_eq!(actual_json_value, expect);
+}
+
+#[test]
+// Test when testcase has multiple failures with array shape
+fn multiple_failures_array_shape() {
+    let xml = r#"
+        <?xml version="1.0" encoding="UTF-8"?>
+        <testsuite>
+            <testcase>
+                <failure message="failure1" />
+                <failure message="failure2" />
+            </testcase>
+        </testsuite>
+    "#;
+    let expect = json!({
+        "testsuite": {
+            "testcase": [
+                {
+                    "failure": [
+                        {
+                            "message": "failure1"
+                        },
+                        {
+                            "message": "failure2"
+                        }
+                    ]
+                }
+            ]
+        }
+    });
+
+    let actual = junit2json::from_str(xml).unwrap();
+    let actual_json_value = actual.to_json_value(&DetailShape::Array).unwrap();
+    assert_eq!(actual_json_value, expect);
+}
+
+#[test]
+// Test that serializing with serde directly keeps all failures
+fn multiple_failures_serde() {
+    let xml = r#"
+        <?xml version="1.0" encoding="UTF-8"?>
+        <testsuite>
+            <testcase>
+                <failure message="failure1" />
+                <failure message="failure2" />
+            </testcase>
+        </testsuite>
+    "#;
+    let actual = junit2json::from_str(xml).unwrap();
+    assert_eq!(
+        serde_json::to_value(&actual).unwrap(),
+        actual.to_json_value(&DetailShape::Array).unwrap()
+    );
+}
@@ -1,3 +1,4 @@
+use junit2json::cli::DetailShape;
 use std::fs::File;
 use std::io::BufReader;
 
@@ -6,7 +7,10 @@ fn fixtures_snapshot() {
     insta::glob!("fixtures/*.xml", |path| {
         let file = File::open(path).unwrap();
         let reader = BufReader::new(file);
-        let testsuites = junit2json::from_reader(reader).unwrap();
+        let testsuites = junit2json::from_reader(reader)
+            .unwrap()
+            .to_json_value(&DetailShape::Object)
+            .unwrap();
 
         insta::assert_json_snapshot!(testsuites)
     })
@@ -0,0 +1,338 @@
+use junit2json::cli::DetailShape;
+use junit2json::*;
+use pretty_assertions::assert_eq;
+use std::fs::File;
+use std::io::BufReader;
+use std::sync::Arc;
+
+fn collect_testcases(testsuite: &TestSuite, testcases: &mut Vec<TestCase>) {
+    if let Some(testcase) = &testsuite.testcase {
+        testcases.extend(testcase.iter().cloned());
+    }
+    if let Some(nested) = &testsuite.testsuite {
+        nested
+            .iter()
+            .for_each(|item| collect_testcases(item, testcases));
+    }
+}
+
+#[test]
+// Test that streaming yields the same testcases as from_reader for all fixtures
+fn same_testcases_as_from_reader() {
+    for entry in std::fs::read_dir("tests/fixtures").unwrap() {
+        let path = entry.unwrap().path();
+        let mut expected = vec![];
+        match from_reader(BufReader::new(File::open(&path).unwrap())).unwrap() {
+            TestSuitesOrTestSuite::TestSuites(testsuites) => testsuites
+                .testsuite
+                .unwrap_or_default()
+                .iter()
+                .for_each(|item| collect_testcases(item, &mut expected)),
+            TestSuitesOrTestSuite::TestSuite(testsuite) => {
+                collect_testcases(&testsuite, &mut expected)
+            }
+        }
+
+        let actual = stream_from_reader(BufReader::new(File::open(&path).unwrap()))
+            .map(|item| item.unwrap().1)
+            .collect::<Vec<_>>();
+        assert_eq!(expected, actual, "{}", path.display());
+    }
+}
+
+#[test]
+fn suite_context() {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites name="root" tests="3">
+    <testsuite name="suite1" tests="2">
+        <properties>
+            <property name="os" value="linux" />
+        </properties>
+        <testcase name="case1" />
+        <testsuite name="nested1" tests="1">
+            <testcase name="case2" time="0.1" />
+        </testsuite>
+        <system-out>system out text</system-out>
+    </testsuite>
+    <testsuite name="suite2" tests="1">
+        <testcase name="case3" />
+    </testsuite>
+</testsuites>
+"#;
+    let actual = stream_from_reader(BufReader::new(xml.as_bytes()))
+        .map(|item| item.unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(actual.len(), 3);
+
+    let testsuites = Some(TestSuites {
+        name: Some("root".to_string()),
+        tests: Some(3),
+        ..Default::default()
+    });
+    let suite1 = TestSuite {
+        name: Some("suite1".to_string()),
+        tests: Some(2),
+        properties: Some(Properties {
+            property: Some(vec![Property {
+                name: Some("os".to_string()),
+                value: Some("linux".to_string()),
+            }]),
+        }),
+        ..Default::default()
+    };
+    assert_eq!(
+        *actual[0].0,
+        SuiteContext {
+            testsuites: testsuites.clone(),
+            testsuite: vec![suite1.clone()],
+        }
+    );
+    assert_eq!(actual[0].1.name, Some("case1".to_string()));
+    assert_eq!(
+        *actual[1].0,
+        SuiteContext {
+            testsuites: testsuites.clone(),
+            testsuite: vec![
+                suite1,
+                TestSuite {
+                    name: Some("nested1".to_string()),
+                    tests: Some(1),
+                    ..Default::default()
+                }
+            ],
+        }
+    );
+    assert_eq!(actual[1].1.time, Some(0.1));
+    assert_eq!(
+        *actual[2].0,
+        SuiteContext {
+            testsuites,
+            testsuite: vec![TestSuite {
+                name: Some("suite2".to_string()),
+                tests: Some(1),
+                ..Default::default()
+            }],
+        }
+    );
+}
+
+#[test]
+// Test that testcases of the same testsuite share the context
+fn context_is_shared() {
+    let xml = r#"
+<testsuite name="suite1">
+    <testcase name="case1" />
+    <testcase name="case2" />
+</testsuite>
+"#;
+    let actual = stream_from_reader(BufReader::new(xml.as_bytes()))
+        .map(|item| item.unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(actual.len(), 2);
+    assert!(Arc::ptr_eq(&actual[0].0, &actual[1].0));
+    assert_eq!(actual[0].0.testsuites, None);
+    assert_eq!(
+        actual[0].0.parent().and_then(|suite| suite.name.clone()),
+        Some("suite1".to_string())
+    );
+}
+
+#[test]
+fn to_json_value() {
+    let xml = r#"
+<testsuites name="root">
+    <testsuite name="suite1" time="0.1">
+        <testcase name="case1">
+            <failure message="failure1" />
+            <failure message="failure2" />
+        </testcase>
+    </testsuite>
+</testsuites>
+"#;
+    let (context, testcase) = stream_from_reader(BufReader::new(xml.as_bytes()))
+        .next()
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        context
+            .to_json_value(&testcase, &DetailShape::Object)
+            .unwrap(),
+        serde_json::json!({
+            "testsuites": { "name": "root" },
+            "testsuite": [{ "name": "suite1", "time": 0.1 }],
+            "testcase": {
+                "name": "case1",
+                "failure": { "message": "failure1" }
+            }
+        })
+    );
+    assert_eq!(
+        context
+            .to_json_value(&testcase, &DetailShape::Array)
+            .unwrap()["testcase"]["failure"],
+        serde_json::json!([{ "message": "failure1" }, { "message": "failure2" }])
+    );
+}
+
+#[test]
+// Test that the error has the location in the whole XML, not in the testcase
+fn invalid_number() {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites>
+    <testsuite name="suite1">
+        <testcase name="case1" time="0.1" />
+    </testsuite>
+    <testsuite name="suite2">
+        <testcase name="case1" time="0.1" />
+        <testcase name="case2" time="N/A" />
+    </testsuite>
+</testsuites>
+"#;
+    let mut stream = stream_from_reader(BufReader::new(xml.as_bytes()));
+    assert!(stream.next().unwrap().is_ok());
+    assert!(stream.next().unwrap().is_ok());
+    let actual = stream.next().unwrap().unwrap_err();
+    match actual {
+        Error::InvalidNumber {
+            ref value,
+            ref location,
+            ..
+        } => {
+            assert_eq!(value, "N/A");
+            assert_eq!(location.line, 8);
+            assert_eq!(location.column, 9);
+            assert_eq!(
+                location.path,
+                "testsuites/testsuite[2]/testcase[2]/@time".to_string()
+            );
+        }
+        _ => panic!("unexpected error: {:?}", actual),
+    }
+    assert!(stream.next().is_none());
+}
+
+#[test]
+// Test that the testcases before the end are yielded when XML is truncated
+fn truncated() {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites>
+    <testsuite name="suite1">
+        <testcase name="case1" />
+        <testcase name="case2">
+            <system-out>system out
+"#;
+    let mut stream = stream_from_reader(BufReader::new(xml.as_bytes()));
+    assert_eq!(
+        stream.next().unwrap().unwrap().1.name,
+        Some("case1".to_string())
+    );
+    match stream.next().unwrap().unwrap_err() {
+        Error::Syntax { location, .. } => {
+            assert_eq!(location.line, 7);
+            assert_eq!(location.path, "testsuites/testsuite[1]/testcase[2]");
+        }
+        err => panic!("unexpected error: {:?}", err),
+    }
+    assert!(stream.next().is_none());
+}
+
+#[test]
+fn unexpected_root() {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<unrecognized />
+"#;
+    let actual = stream_from_reader(BufReader::new(xml.as_bytes()))
+        .next()
+        .unwrap()
+        .unwrap_err();
+    match actual {
+        Error::UnexpectedRoot { name, location } => {
+            assert_eq!(name, "unrecognized");
+            assert_eq!(
+                location,
+                Location {
+                    offset: 39,
+                    line: 2,
+                    column: 1,
+                    path: "unrecognized".to_string(),
+                }
+            );
+        }
+        _ => panic!("unexpected error: {:?}", actual),
+    }
+}
+
+#[test]
+fn flatten() {
+    let xml = r#"
+<testsuites name="root">
+    <testsuite name="suite1" timestamp="2024-01-01T00:00:00" hostname="host1">
+        <properties>
+            <property name="os" value="linux" />
+        </properties>
+        <testsuite name="nested1">
+            <testcase name="case1" time="0.1">
+                <failure message="failure1" />
+                <failure message="failure2" />
+            </testcase>
+        </testsuite>
+    </testsuite>
+</testsuites>
+"#;
+    let (context, testcase) = stream_from_reader(BufReader::new(xml.as_bytes()))
+        .next()
+        .unwrap()
+        .unwrap();
+    let actual = context.flatten(testcase);
+    assert_eq!(actual.testsuites_name, Some("root".to_string()));
+    assert_eq!(actual.testsuite_name, Some("nested1".to_string()));
+    assert_eq!(
+        actual.testsuite_timestamp,
+        Some("2024-01-01T00:00:00".to_string())
+    );
+    assert_eq!(actual.testsuite_hostname, Some("host1".to_string()));
+    assert_eq!(
+        actual.testsuite_properties,
+        Some(vec![Property {
+            name: Some("os".to_string()),
+            value: Some("linux".to_string()),
+        }])
+    );
+    assert_eq!(
+        actual.to_json_value(&DetailShape::Object).unwrap(),
+        serde_json::json!({
+            "testsuites_name": "root",
+            "testsuite_name": "nested1",
+            "testsuite_timestamp": "2024-01-01T00:00:00",
+            "testsuite_hostname": "host1",
+            "testsuite_properties": [{ "name": "os", "value": "linux" }],
+            "name": "case1",
+            "time": 0.1,
+            "failure": { "message": "failure1" }
+        })
+    );
+}
+
+#[test]
+// Test that the root testsuite without testsuites has no testsuites_name
+fn flatten_testsuite_root() {
+    let xml = r#"
+<testsuite name="suite1">
+    <testcase name="case1" />
+</testsuite>
+"#;
+    let (context, testcase) = stream_from_reader(BufReader::new(xml.as_bytes()))
+        .next()
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        context
+            .flatten(testcase)
+            .to_json_value(&DetailShape::Array)
+            .unwrap(),
+        serde_json::json!({
+            "testsuite_name": "suite1",
+            "name": "case1"
+        })
+    );
+}
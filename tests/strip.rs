@@ -0,0 +1,100 @@
+use junit2json::cli::{DetailShape, StripMode};
+use junit2json::*;
+use pretty_assertions::assert_eq;
+
+fn testcase(xml: &str, mode: &StripMode) -> TestCase {
+    let mut testsuite = from_str(xml).unwrap();
+    testsuite.strip_texts(mode);
+    let TestSuitesOrTestSuite::TestSuite(testsuite) = testsuite else {
+        panic!("not testsuite");
+    };
+    testsuite.testcase.unwrap().remove(0)
+}
+
+#[test]
+// Test that CSI, OSC and other escape sequences are stripped
+fn strip_escape_sequences() {
+    let xml = r#"
+      <testsuite name="suite1">
+          <testcase name="case1">
+              <system-out>&#27;[1;31mred&#27;[0m &#27;]8;;https://example.com&#7;link&#27;]8;;&#7; &#27;(Bcharset &#27;[2Kcleared</system-out>
+              <failure message="&#27;[31mfailed&#27;[0m">&#155;32mgreen</failure>
+          </testcase>
+      </testsuite>
+    "#;
+    let actual = testcase(xml, &StripMode::Ansi);
+    assert_eq!(
+        actual.system_out,
+        Some(vec!["red link charset cleared".to_string()])
+    );
+    let failure = &actual.failure.unwrap()[0];
+    assert_eq!(failure.message, Some("failed".to_string()));
+    assert_eq!(failure.inner, Some("green".to_string()));
+}
+
+#[test]
+// Test that SGR sequences without ESC are stripped with OrphanSgr, but brackets of other texts are kept
+fn strip_orphan_sgr() {
+    let xml = r#"
+      <testsuite name="suite1">
+          <testcase name="case1">
+              <system-out>[1mDiff[0m [31m&lt; left[0m / [32mright &gt;[0m : array[m] [0] [1;2] [ms] [1234m</system-out>
+          </testcase>
+      </testsuite>
+    "#;
+    let actual = testcase(xml, &StripMode::OrphanSgr);
+    assert_eq!(
+        actual.system_out,
+        Some(vec![
+            "Diff < left / right > : array[m] [0] [1;2] [ms] [1234m".to_string()
+        ])
+    );
+}
+
+#[test]
+// Test that ordinary texts in brackets are kept by Ansi, and ones followed by `]` by OrphanSgr too
+fn keep_bracketed_text() {
+    let xml = r#"
+      <testsuite name="suite1">
+          <testcase name="case1">
+              <system-out>retry in [5m] then [0m done; list[1m]</system-out>
+          </testcase>
+      </testsuite>
+    "#;
+    let actual = testcase(xml, &StripMode::Ansi);
+    assert_eq!(
+        actual.system_out,
+        Some(vec!["retry in [5m] then [0m done; list[1m]".to_string()])
+    );
+    let actual = testcase(xml, &StripMode::OrphanSgr);
+    assert_eq!(
+        actual.system_out,
+        Some(vec!["retry in [5m] then  done; list[1m]".to_string()])
+    );
+}
+
+#[test]
+// Test that control characters except tab, line feed and carriage return are stripped only with Control
+fn strip_control_characters() {
+    let xml = "<testsuite name=\"suite1\"><testcase name=\"case1\"><system-err>a\u{8}\tb\r\nc\u{7f}&#27;[0m</system-err></testcase></testsuite>";
+    let actual = testcase(xml, &StripMode::Ansi);
+    assert_eq!(
+        actual.system_err,
+        Some(vec!["a\u{8}\tb\r\nc\u{7f}".to_string()])
+    );
+    let actual = testcase(xml, &StripMode::Control);
+    assert_eq!(actual.system_err, Some(vec!["a\tb\r\nc".to_string()]));
+}
+
+#[test]
+// Test that the fixture of cargo-nextest has no escape sequences after stripping
+fn strip_fixture() {
+    let xml = std::fs::read_to_string("tests/fixtures/cargo-nextest.xml").unwrap();
+    let mut testsuites = from_str(&xml).unwrap();
+    testsuites.strip_texts(&StripMode::OrphanSgr);
+    let json = testsuites.to_json_value(&DetailShape::Object).unwrap();
+    let json = json.to_string();
+    assert!(!json.contains("[31m"));
+    assert!(!json.contains("[0m"));
+    assert!(json.contains("Diff < left / right > :"));
+}
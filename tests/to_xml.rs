@@ -0,0 +1,80 @@
+use junit2json::*;
+use pretty_assertions::assert_eq;
+
+#[test]
+// Test that every fixture is the same after XML -> struct -> XML -> struct
+fn fixtures_round_trip() {
+    insta::glob!("fixtures/*.xml", |path| {
+        let xml = std::fs::read_to_string(path).unwrap();
+        for options in [ParseOptions::default(), ParseOptions { lossless: true }] {
+            let expected = from_str_with_options(&xml, &options).unwrap();
+            let written = to_string(&expected).unwrap();
+            let actual = from_str_with_options(&written, &options).unwrap();
+            assert_eq!(expected, actual, "{}\n{}", path.display(), written);
+        }
+    })
+}
+
+#[test]
+fn testsuites() {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites name="root" tests="2" time="0.5">
+    <testsuite name="suite1" tests="2">
+        <properties>
+            <property name="os" value="linux" />
+        </properties>
+        <testcase name="case1" time="0.1" />
+        <testcase name="case2" classname="Case2">
+            <failure message="expected &lt;1&gt;&#xA;but was 2" type="AssertionError">stack &amp; trace</failure>
+            <system-out>system out text</system-out>
+        </testcase>
+    </testsuite>
+</testsuites>
+"#;
+    let testsuites = from_str(xml).unwrap();
+    let expected = r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites name="root" time="0.5" tests="2">
+  <testsuite name="suite1" tests="2">
+    <properties>
+      <property name="os" value="linux"/>
+    </properties>
+    <testcase name="case1" time="0.1"/>
+    <testcase name="case2" classname="Case2">
+      <failure message="expected &lt;1&gt;&#xA;but was 2" type="AssertionError">stack &amp; trace</failure>
+      <system-out>system out text</system-out>
+    </testcase>
+  </testsuite>
+</testsuites>
+"#;
+    assert_eq!(to_string(&testsuites).unwrap(), expected);
+}
+
+#[test]
+fn testsuite_root_with_extra() {
+    let xml = r#"
+<testsuite name="suite1" uuid="d241b926">
+    <testcase name="case1">
+        <rerunFailure message="failed at first run">
+            <stackTrace>stack trace</stackTrace>
+        </rerunFailure>
+        <attachments>
+            <attachment path="screenshot.png">screenshot</attachment>
+        </attachments>
+    </testcase>
+</testsuite>
+"#;
+    let testsuite = from_str_with_options(xml, &ParseOptions { lossless: true }).unwrap();
+    let expected = r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuite name="suite1" uuid="d241b926">
+  <testcase name="case1">
+    <rerunFailure message="failed at first run">
+      <stackTrace>stack trace</stackTrace>
+    </rerunFailure>
+    <attachments>
+      <attachment path="screenshot.png">screenshot</attachment>
+    </attachments>
+  </testcase>
+</testsuite>
+"#;
+    assert_eq!(to_string(&testsuite).unwrap(), expected);
+}
@@ -0,0 +1,97 @@
+use junit2json::cli::TruncateKeep;
+use junit2json::*;
+use pretty_assertions::assert_eq;
+
+fn testcase(xml: &str, options: &TruncateOptions) -> TestCase {
+    let mut testsuite = from_str(xml).unwrap();
+    testsuite.truncate_texts(options);
+    let TestSuitesOrTestSuite::TestSuite(testsuite) = testsuite else {
+        panic!("not testsuite");
+    };
+    testsuite.testcase.unwrap().remove(0)
+}
+
+#[test]
+// Test that head, tail and both of system-out are kept within the limit
+fn truncate_system_out() {
+    let xml = r#"
+      <testsuite name="suite1">
+          <testcase name="case1">
+              <system-out>0123456789abcdefghijklmnopqrstuvwxyz0123456789</system-out>
+              <system-err>short</system-err>
+          </testcase>
+      </testsuite>
+    "#;
+    let marker = "[... truncated from 46 bytes ...]";
+    for (keep, expected) in [
+        (TruncateKeep::Head, format!("012345{}", marker)),
+        (TruncateKeep::Tail, format!("{}456789", marker)),
+        (TruncateKeep::Both, format!("012{}789", marker)),
+    ] {
+        let options = TruncateOptions {
+            max_output_bytes: Some(marker.len() + 6),
+            keep,
+            ..Default::default()
+        };
+        let actual = testcase(xml, &options);
+        assert_eq!(actual.system_out, Some(vec![expected]));
+        assert_eq!(actual.system_err, Some(vec!["short".to_string()]));
+    }
+}
+
+#[test]
+// Test that multi-byte characters are not split
+fn truncate_at_char_boundary() {
+    let xml = r#"
+      <testsuite name="suite1">
+          <testcase name="case1">
+              <failure message="あいうえおあいうえおあいうえおあいうえお">かきくけこかきくけこかきくけこかきくけこ</failure>
+          </testcase>
+      </testsuite>
+    "#;
+    let marker = "[... truncated from 60 bytes ...]";
+    let options = TruncateOptions {
+        // 7 bytes are left for the text, that is 2 characters and 1 byte
+        max_detail_bytes: Some(marker.len() + 7),
+        keep: TruncateKeep::Both,
+        ..Default::default()
+    };
+    let actual = testcase(xml, &options);
+    let failure = &actual.failure.unwrap()[0];
+    assert_eq!(failure.message, Some(format!("あ{}お", marker)));
+    assert_eq!(failure.inner, Some(format!("か{}こ", marker)));
+}
+
+#[test]
+// Test that the limits are independent
+fn truncate_only_detail() {
+    let xml = r#"
+      <testsuite name="suite1">
+          <system-out>0123456789abcdefghijklmnopqrstuvwxyz0123456789</system-out>
+          <testcase name="case1">
+              <flakyFailure message="0123456789abcdefghijklmnopqrstuvwxyz0123456789">
+                  <system-out>0123456789abcdefghijklmnopqrstuvwxyz0123456789</system-out>
+              </flakyFailure>
+          </testcase>
+      </testsuite>
+    "#;
+    let options = TruncateOptions {
+        max_detail_bytes: Some(40),
+        ..Default::default()
+    };
+    let mut testsuite = from_str(xml).unwrap();
+    testsuite.truncate_texts(&options);
+    let TestSuitesOrTestSuite::TestSuite(testsuite) = testsuite else {
+        panic!("not testsuite");
+    };
+    assert_eq!(testsuite.system_out.as_ref().unwrap()[0].len(), 46);
+    let rerun = &testsuite.testcase.as_ref().unwrap()[0]
+        .flaky_failure
+        .as_ref()
+        .unwrap()[0];
+    assert_eq!(
+        rerun.message,
+        Some("0123456[... truncated from 46 bytes ...]".to_string())
+    );
+    assert_eq!(rerun.system_out.as_ref().unwrap()[0].len(), 46);
+}